@@ -0,0 +1,279 @@
+use std::collections::VecDeque;
+
+/// A trait for pulling a single bit out of an integer value by index.
+///
+/// # Methods
+///
+/// * 'bit_index' - Accepts a u8 index (counted from the least significant
+///         bit, starting at 0) and returns the bit at that position as
+///         either a 0 or 1.
+pub trait BitIndex {
+    /// Returns the bit at 'index' (0 = least significant bit) as a u8.
+    fn bit_index(&self, index: u8) -> u8;
+}
+
+impl BitIndex for u32 {
+    fn bit_index(&self, index: u8) -> u8 {
+        ((self >> index) & 1) as u8
+    }
+}
+
+impl BitIndex for u16 {
+    fn bit_index(&self, index: u8) -> u8 {
+        ((self >> index) & 1) as u8
+    }
+}
+
+impl BitIndex for u8 {
+    fn bit_index(&self, index: u8) -> u8 {
+        (self >> index) & 1
+    }
+}
+
+/// A FIFO queue of individual bits, read and written most significant bit
+/// first. Codes (see 'prefix::Code') are pushed into a BitStream one bit
+/// at a time from their most significant bit down to their least
+/// significant bit, which is also the order DEFLATE expects Huffman codes
+/// to be read back out in, so a BitStream doubles as the working
+/// representation of a DEFLATE bit sequence on both the encode and decode
+/// sides.
+///
+/// # Fields
+///
+/// * 'bits' - The underlying queue of individual bits.
+/// * 'consumed' - The number of bits popped off the front of the stream
+///         since it was created, used to track byte alignment.
+///
+/// # Methods
+///
+/// * 'new' - Creates an empty BitStream.
+/// * 'from_bytes' - Builds a BitStream from raw bytes, unpacking each byte
+///         least-significant-bit first, per RFC 1951's packing order.
+/// * 'push' - Pushes 'length' bits of 'buffer' onto the back of the
+///         stream, most significant bit first.
+/// * 'pop' - Pops a single bit off of the front of the stream.
+/// * 'pop_bits' - Pops 'count' bits off of the front of the stream and
+///         assembles them into an integer, first bit popped becomes the
+///         least significant bit (the order DEFLATE uses for extra-bits
+///         fields).
+/// * 'align_to_byte' - Discards bits until the stream is aligned on a byte
+///         boundary relative to its start, as required before a stored
+///         block.
+/// * 'into_bytes' - Packs the remaining bits into bytes, least significant
+///         bit first, zero-padding the final byte if necessary.
+#[derive(Debug, Default)]
+pub struct BitStream {
+    bits: VecDeque<u8>,
+    consumed: usize,
+    pushed: usize,
+}
+
+impl BitStream {
+    /// Creates a new, empty BitStream.
+    pub fn new() -> Self {
+        Self {
+            bits: VecDeque::new(),
+            consumed: 0,
+            pushed: 0,
+        }
+    }
+
+    /// Unpacks 'bytes' into a BitStream, each byte contributing its bits
+    /// least-significant-bit first, matching the bit order DEFLATE packs
+    /// its stream in.
+    ///
+    /// # Arguments
+    ///
+    /// * 'bytes' - The raw bytes to unpack.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut stream = Self::new();
+        stream.extend_bytes(bytes);
+        stream
+    }
+
+    /// Appends 'bytes' to the back of the stream, each byte contributing
+    /// its bits least-significant-bit first, matching 'from_bytes'. Used
+    /// to feed a stream with more input after it has already been
+    /// partially read, for incremental decoding.
+    ///
+    /// # Arguments
+    ///
+    /// * 'bytes' - The raw bytes to append.
+    pub fn extend_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            for i in 0..8 {
+                self.bits.push_back(byte.bit_index(i));
+            }
+        }
+    }
+
+    /// Pushes 'length' bits of 'buffer' onto the back of the stream, most
+    /// significant bit first.
+    ///
+    /// # Arguments
+    ///
+    /// * 'buffer' - The bits to push.
+    /// * 'length' - How many of the low bits of 'buffer' to push.
+    pub fn push(&mut self, buffer: u32, length: u8) {
+        for i in (0..length).rev() {
+            self.bits.push_back(buffer.bit_index(i));
+        }
+        self.pushed += length as usize;
+    }
+
+    /// Pushes 'length' bits of 'buffer' onto the back of the stream, least
+    /// significant bit first. This is the order DEFLATE uses for its
+    /// "plain" integer fields (BTYPE, HLIT, HDIST, extra-bits counts, the
+    /// stored-block LEN/NLEN words, ...), as opposed to Huffman codes,
+    /// which are written most significant bit first via 'push'.
+    ///
+    /// # Arguments
+    ///
+    /// * 'buffer' - The bits to push.
+    /// * 'length' - How many of the low bits of 'buffer' to push.
+    pub fn push_lsb(&mut self, buffer: u32, length: u8) {
+        for i in 0..length {
+            self.bits.push_back(buffer.bit_index(i));
+        }
+        self.pushed += length as usize;
+    }
+
+    /// Appends every bit remaining in 'other' onto the back of this
+    /// stream, in order, regardless of either stream's byte alignment.
+    /// Used to concatenate independently-built block streams (e.g. from
+    /// 'parallel::ParallelEncoder') into one DEFLATE stream without
+    /// forcing a byte boundary between them.
+    ///
+    /// # Arguments
+    ///
+    /// * 'other' - The stream to drain and append.
+    pub fn append(&mut self, mut other: BitStream) {
+        self.pushed += other.bits.len();
+        self.bits.append(&mut other.bits);
+    }
+
+    /// Pads the stream with 0 bits until the number of bits pushed so far
+    /// is a multiple of 8, mirroring the padding DEFLATE inserts before a
+    /// stored block.
+    pub fn align_push_to_byte(&mut self) {
+        let remainder = self.pushed % 8;
+        if remainder != 0 {
+            self.push(0, (8 - remainder) as u8);
+        }
+    }
+
+    /// Pops a single bit off of the front of the stream, or None if the
+    /// stream is empty.
+    pub fn pop(&mut self) -> Option<u8> {
+        let bit = self.bits.pop_front();
+        if bit.is_some() {
+            self.consumed += 1;
+        }
+        bit
+    }
+
+    /// Pops 'count' bits off of the front of the stream and assembles them
+    /// into a u32, with the first bit popped becoming the least
+    /// significant bit of the result. Returns None if the stream runs out
+    /// of bits before 'count' is reached.
+    ///
+    /// # Arguments
+    ///
+    /// * 'count' - The number of bits to pop.
+    pub fn pop_bits(&mut self, count: u8) -> Option<u32> {
+        let mut value = 0u32;
+        for i in 0..count {
+            value |= (self.pop()? as u32) << i;
+        }
+        Some(value)
+    }
+
+    /// Discards bits from the front of the stream until 'consumed' is a
+    /// multiple of 8, aligning the stream on a byte boundary.
+    pub fn align_to_byte(&mut self) {
+        let remainder = self.consumed % 8;
+        if remainder != 0 {
+            for _ in 0..(8 - remainder) {
+                self.pop();
+            }
+        }
+    }
+
+    /// Returns the number of bits remaining in the stream.
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Returns how many bits would need to be popped to reach the next
+    /// byte boundary relative to 'consumed', without popping them. Lets
+    /// a caller check whether enough bits are buffered to align the
+    /// stream before actually discarding any, which 'align_to_byte'
+    /// cannot do on its own.
+    pub fn bits_until_byte_boundary(&self) -> u8 {
+        let remainder = self.consumed % 8;
+        if remainder == 0 {
+            0
+        } else {
+            (8 - remainder) as u8
+        }
+    }
+
+    /// Returns true if the stream has no bits left.
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Packs the remaining bits into bytes, least significant bit first,
+    /// padding the final byte with zero bits if it is not full.
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.bits.len().div_ceil(8));
+        while !self.bits.is_empty() {
+            let mut byte = 0u8;
+            for i in 0..8 {
+                let bit = self.bits.pop_front().unwrap_or(0);
+                byte |= bit << i;
+            }
+            bytes.push(byte);
+        }
+        bytes
+    }
+}
+
+impl Iterator for BitStream {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        self.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitIndex, BitStream};
+
+    #[test]
+    fn test_bit_index() {
+        let value: u32 = 0b1011;
+
+        assert_eq!(value.bit_index(0), 1);
+        assert_eq!(value.bit_index(1), 1);
+        assert_eq!(value.bit_index(2), 0);
+        assert_eq!(value.bit_index(3), 1);
+    }
+
+    #[test]
+    fn test_from_bytes_round_trips_into_bytes() {
+        let bytes = vec![0b1010_0110, 0b0000_1111];
+        let stream = BitStream::from_bytes(&bytes);
+
+        assert_eq!(stream.into_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_pop_bits_matches_push_order() {
+        let mut stream = BitStream::new();
+        stream.push(0b101, 3);
+
+        assert_eq!(stream.pop_bits(3), Some(0b101));
+    }
+}
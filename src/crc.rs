@@ -0,0 +1,36 @@
+/// Computes the CRC-32 checksum gzip trailers use (the same
+/// polynomial and reflection as zlib and PNG, ISO 3309 / ITU-T V.42),
+/// bit by bit rather than via a lookup table.
+///
+/// # Arguments
+///
+/// * 'data' - The bytes to checksum.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::crc32;
+
+    #[test]
+    fn test_crc32_known_value() {
+        // The canonical "check" value for this CRC-32 variant.
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_crc32_empty() {
+        assert_eq!(crc32(b""), 0);
+    }
+}
@@ -0,0 +1,519 @@
+use std::fmt::Display;
+
+use crate::bitstream::BitStream;
+use crate::prefix::{PrefixTree, FIXED_CODE_LENGTHS, FIXED_DISTANCE_LENGTHS};
+
+/// The base length and number of extra bits for each length code, indexed
+/// by 'code - 257' (RFC 1951 section 3.2.5).
+pub(crate) const LENGTH_TABLE: [(u16, u8); 29] = [
+    (3, 0),
+    (4, 0),
+    (5, 0),
+    (6, 0),
+    (7, 0),
+    (8, 0),
+    (9, 0),
+    (10, 0),
+    (11, 1),
+    (13, 1),
+    (15, 1),
+    (17, 1),
+    (19, 2),
+    (23, 2),
+    (27, 2),
+    (31, 2),
+    (35, 3),
+    (43, 3),
+    (51, 3),
+    (59, 3),
+    (67, 4),
+    (83, 4),
+    (99, 4),
+    (115, 4),
+    (131, 5),
+    (163, 5),
+    (195, 5),
+    (227, 5),
+    (258, 0),
+];
+
+/// The base distance and number of extra bits for each distance code,
+/// indexed by the code itself (RFC 1951 section 3.2.5).
+pub(crate) const DISTANCE_TABLE: [(u16, u8); 30] = [
+    (1, 0),
+    (2, 0),
+    (3, 0),
+    (4, 0),
+    (5, 1),
+    (7, 1),
+    (9, 2),
+    (13, 2),
+    (17, 3),
+    (25, 3),
+    (33, 4),
+    (49, 4),
+    (65, 5),
+    (97, 5),
+    (129, 6),
+    (193, 6),
+    (257, 7),
+    (385, 7),
+    (513, 8),
+    (769, 8),
+    (1025, 9),
+    (1537, 9),
+    (2049, 10),
+    (3073, 10),
+    (4097, 11),
+    (6145, 11),
+    (8193, 12),
+    (12289, 12),
+    (16385, 13),
+    (24577, 13),
+];
+
+/// Maps an LZ77 match length to its DEFLATE length code, the number of
+/// extra bits that follow it, and the value those extra bits must carry
+/// (the length's offset from the code's base length).
+///
+/// # Arguments
+///
+/// * 'length' - A match length in the range 3..=258.
+pub(crate) fn length_code(length: u16) -> (u16, u8, u16) {
+    // The maximum length 258 gets its own code (285) with no extra bits.
+    // It must be checked before the loop below: the (227, 5) entry's
+    // nominal 32-value span would otherwise swallow it, since RFC 1951
+    // reserves only 31 of those 32 extra-bit values (0..=30) for code
+    // 284, leaving 258 to code 285 alone.
+    if length == 258 {
+        return (285, 0, 0);
+    }
+
+    for (index, &(base, extra_bits)) in LENGTH_TABLE.iter().enumerate() {
+        let span = 1u16 << extra_bits;
+        if length >= base && length < base + span {
+            return (257 + index as u16, extra_bits, length - base);
+        }
+    }
+    unreachable!("length {length} out of DEFLATE's 3..=258 range")
+}
+
+/// Maps an LZ77 match distance to its DEFLATE distance code, the number
+/// of extra bits that follow it, and the value those extra bits must
+/// carry (the distance's offset from the code's base distance).
+///
+/// # Arguments
+///
+/// * 'distance' - A match distance in the range 1..=32768.
+pub(crate) fn distance_code(distance: u16) -> (u16, u8, u16) {
+    for (index, &(base, extra_bits)) in DISTANCE_TABLE.iter().enumerate() {
+        let span = 1u16 << extra_bits;
+        if distance >= base && distance < base + span {
+            return (index as u16, extra_bits, distance - base);
+        }
+    }
+    unreachable!("distance {distance} out of DEFLATE's 1..=32768 range")
+}
+
+/// The order in which code length code lengths are stored in a dynamic
+/// Huffman block (RFC 1951 section 3.2.7).
+pub const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Errors that can occur while parsing or inflating a DEFLATE stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeflateError {
+    /// The bit stream ran out of bits mid-block.
+    UnexpectedEnd,
+    /// A stored block's LEN and NLEN (one's complement of LEN) fields
+    /// disagreed.
+    BadStoredLength,
+    /// A block header used the reserved block type (0b11).
+    ReservedBlockType,
+    /// A Huffman code did not resolve to a symbol, meaning the bit stream
+    /// does not match the tree it was decoded against.
+    InvalidCode,
+    /// A back-reference pointed further back than any data decompressed
+    /// so far.
+    InvalidDistance,
+}
+
+impl Display for DeflateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeflateError::UnexpectedEnd => write!(f, "ran out of bits mid-block"),
+            DeflateError::BadStoredLength => write!(f, "stored block LEN/NLEN mismatch"),
+            DeflateError::ReservedBlockType => write!(f, "reserved block type 0b11"),
+            DeflateError::InvalidCode => write!(f, "huffman code did not resolve to a symbol"),
+            DeflateError::InvalidDistance => write!(f, "back-reference distance out of range"),
+        }
+    }
+}
+
+impl std::error::Error for DeflateError {}
+
+/// The three block types DEFLATE can emit, alongside their on-the-wire
+/// BTYPE value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockType {
+    Stored,
+    Fixed,
+    Dynamic,
+}
+
+/// A single parsed DEFLATE block.
+///
+/// # Fields
+///
+/// * 'is_final' - Whether this was the last block in the stream (BFINAL).
+/// * 'block_type' - Which of the three block kinds this is (BTYPE).
+/// * 'tokens' - For Fixed/Dynamic blocks, the literal/length/distance
+///         tokens making up the block, decoded but not yet resolved
+///         against the sliding window. For Stored blocks, empty (the raw
+///         bytes live in 'stored' instead).
+/// * 'stored' - For Stored blocks, the raw uncompressed bytes.
+#[derive(Debug, Clone)]
+struct Block {
+    is_final: bool,
+    block_type: BlockType,
+    tokens: Vec<Token>,
+    stored: Vec<u8>,
+}
+
+/// A single decoded DEFLATE symbol: either a literal byte or a
+/// length/distance back-reference into the sliding window.
+#[derive(Debug, Clone, Copy)]
+enum Token {
+    Literal(u8),
+    Match { length: u16, distance: u16 },
+}
+
+/// A fully parsed DEFLATE stream: a sequence of blocks ready to be
+/// expanded against a sliding window.
+///
+/// # Fields
+///
+/// * 'blocks' - The parsed blocks, in stream order.
+#[derive(Debug, Clone)]
+pub struct DeflateBlock {
+    blocks: Vec<Block>,
+}
+
+impl DeflateBlock {
+    /// Parses a raw DEFLATE stream out of 'buffer'. Parsing stops as soon
+    /// as a block with BFINAL set has been read; any trailing bytes (such
+    /// as a gzip trailer) are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * 'buffer' - The raw DEFLATE-compressed bytes.
+    ///
+    /// # Returns
+    ///
+    /// A DeflateBlock holding every parsed block, or a DeflateError if the
+    /// stream is malformed.
+    pub fn build(buffer: &[u8]) -> Result<Self, DeflateError> {
+        Ok(Self::build_prefix(buffer)?.0)
+    }
+
+    /// Like 'build', but also returns how many leading bytes of 'buffer'
+    /// the DEFLATE stream actually occupied, rounded up to the next byte
+    /// boundary. Used by 'gzip::GzipFile' to locate the trailer (CRC32 +
+    /// ISIZE) that immediately follows, and to find where the next
+    /// member begins in a concatenated gzip stream.
+    ///
+    /// # Arguments
+    ///
+    /// * 'buffer' - The raw DEFLATE-compressed bytes, which may have
+    ///         trailing bytes (a trailer, another member, ...) beyond the
+    ///         stream itself.
+    ///
+    /// # Returns
+    ///
+    /// The parsed DeflateBlock and the number of bytes of 'buffer' it
+    /// consumed, or a DeflateError if the stream is malformed.
+    pub fn build_prefix(buffer: &[u8]) -> Result<(Self, usize), DeflateError> {
+        let mut stream = BitStream::from_bytes(buffer);
+        let mut blocks = Vec::new();
+
+        loop {
+            let block = parse_block(&mut stream)?;
+            let is_final = block.is_final;
+            blocks.push(block);
+            if is_final {
+                break;
+            }
+        }
+
+        let consumed_bits = buffer.len() * 8 - stream.len();
+        Ok((Self { blocks }, consumed_bits.div_ceil(8)))
+    }
+
+    /// Expands every parsed block against a single 32 KiB sliding window,
+    /// producing the fully decompressed data.
+    pub fn decompress(&self) -> Result<Vec<u8>, DeflateError> {
+        let mut output = Vec::new();
+
+        for block in &self.blocks {
+            match block.block_type {
+                BlockType::Stored => output.extend_from_slice(&block.stored),
+                BlockType::Fixed | BlockType::Dynamic => {
+                    for token in &block.tokens {
+                        match *token {
+                            Token::Literal(byte) => output.push(byte),
+                            Token::Match { length, distance } => {
+                                let start = output
+                                    .len()
+                                    .checked_sub(distance as usize)
+                                    .ok_or(DeflateError::InvalidDistance)?;
+                                for i in 0..length as usize {
+                                    let byte = output[start + i];
+                                    output.push(byte);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+fn parse_block(stream: &mut BitStream) -> Result<Block, DeflateError> {
+    let is_final = stream.pop().ok_or(DeflateError::UnexpectedEnd)? == 1;
+    let btype = stream.pop_bits(2).ok_or(DeflateError::UnexpectedEnd)?;
+
+    match btype {
+        0b00 => parse_stored(stream, is_final),
+        0b01 => {
+            let tree = PrefixTree::from_lengths(&FIXED_CODE_LENGTHS);
+            let tokens = parse_compressed(stream, tree, None)?;
+            Ok(Block {
+                is_final,
+                block_type: BlockType::Fixed,
+                tokens,
+                stored: Vec::new(),
+            })
+        }
+        0b10 => {
+            let (literal_tree, distance_tree) = parse_dynamic_trees(stream)?;
+            let tokens = parse_compressed(stream, literal_tree, Some(distance_tree))?;
+            Ok(Block {
+                is_final,
+                block_type: BlockType::Dynamic,
+                tokens,
+                stored: Vec::new(),
+            })
+        }
+        _ => Err(DeflateError::ReservedBlockType),
+    }
+}
+
+fn parse_stored(stream: &mut BitStream, is_final: bool) -> Result<Block, DeflateError> {
+    stream.align_to_byte();
+
+    let len = stream.pop_bits(16).ok_or(DeflateError::UnexpectedEnd)? as u16;
+    let nlen = stream.pop_bits(16).ok_or(DeflateError::UnexpectedEnd)? as u16;
+    if len != !nlen {
+        return Err(DeflateError::BadStoredLength);
+    }
+
+    let mut data = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        let byte = stream.pop_bits(8).ok_or(DeflateError::UnexpectedEnd)? as u8;
+        data.push(byte);
+    }
+
+    Ok(Block {
+        is_final,
+        block_type: BlockType::Stored,
+        tokens: Vec::new(),
+        stored: data,
+    })
+}
+
+fn parse_dynamic_trees(stream: &mut BitStream) -> Result<(PrefixTree, PrefixTree), DeflateError> {
+    let hlit = stream.pop_bits(5).ok_or(DeflateError::UnexpectedEnd)? as usize + 257;
+    let hdist = stream.pop_bits(5).ok_or(DeflateError::UnexpectedEnd)? as usize + 1;
+    let hclen = stream.pop_bits(4).ok_or(DeflateError::UnexpectedEnd)? as usize + 4;
+
+    let mut code_length_lengths = [0u8; 19];
+    for &position in CODE_LENGTH_ORDER.iter().take(hclen) {
+        code_length_lengths[position] =
+            stream.pop_bits(3).ok_or(DeflateError::UnexpectedEnd)? as u8;
+    }
+
+    let mut code_length_tree = PrefixTree::from_lengths(&code_length_lengths);
+    let mut lengths = Vec::with_capacity(hlit + hdist);
+
+    while lengths.len() < hlit + hdist {
+        let symbol = decode_symbol(stream, &mut code_length_tree)?;
+        match symbol {
+            0..=15 => lengths.push(symbol as u8),
+            16 => {
+                let repeat = stream.pop_bits(2).ok_or(DeflateError::UnexpectedEnd)? + 3;
+                let previous = *lengths.last().ok_or(DeflateError::InvalidCode)?;
+                for _ in 0..repeat {
+                    lengths.push(previous);
+                }
+            }
+            17 => {
+                let repeat = stream.pop_bits(3).ok_or(DeflateError::UnexpectedEnd)? + 3;
+                lengths.extend(std::iter::repeat_n(0u8, repeat as usize));
+            }
+            18 => {
+                let repeat = stream.pop_bits(7).ok_or(DeflateError::UnexpectedEnd)? + 11;
+                lengths.extend(std::iter::repeat_n(0u8, repeat as usize));
+            }
+            _ => return Err(DeflateError::InvalidCode),
+        }
+    }
+
+    let literal_tree = PrefixTree::from_lengths(&lengths[..hlit]);
+    let distance_tree = PrefixTree::from_lengths(&lengths[hlit..hlit + hdist]);
+
+    Ok((literal_tree, distance_tree))
+}
+
+fn parse_compressed(
+    stream: &mut BitStream,
+    mut literal_tree: PrefixTree,
+    distance_tree: Option<PrefixTree>,
+) -> Result<Vec<Token>, DeflateError> {
+    let mut distance_tree =
+        distance_tree.unwrap_or_else(|| PrefixTree::from_lengths(&FIXED_DISTANCE_LENGTHS));
+    let mut tokens = Vec::new();
+
+    loop {
+        let symbol = decode_symbol(stream, &mut literal_tree)?;
+        match symbol {
+            0..=255 => tokens.push(Token::Literal(symbol as u8)),
+            256 => break,
+            257..=285 => {
+                let (base, extra_bits) = LENGTH_TABLE[symbol - 257];
+                let extra = if extra_bits > 0 {
+                    stream
+                        .pop_bits(extra_bits)
+                        .ok_or(DeflateError::UnexpectedEnd)? as u16
+                } else {
+                    0
+                };
+                let length = base + extra;
+
+                let distance_symbol = decode_symbol(stream, &mut distance_tree)?;
+                if distance_symbol >= DISTANCE_TABLE.len() {
+                    return Err(DeflateError::InvalidCode);
+                }
+                let (dist_base, dist_extra_bits) = DISTANCE_TABLE[distance_symbol];
+                let dist_extra = if dist_extra_bits > 0 {
+                    stream
+                        .pop_bits(dist_extra_bits)
+                        .ok_or(DeflateError::UnexpectedEnd)? as u16
+                } else {
+                    0
+                };
+                let distance = dist_base + dist_extra;
+
+                tokens.push(Token::Match { length, distance });
+            }
+            _ => return Err(DeflateError::InvalidCode),
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn decode_symbol(stream: &mut BitStream, tree: &mut PrefixTree) -> Result<usize, DeflateError> {
+    loop {
+        let bit = stream.pop().ok_or(DeflateError::UnexpectedEnd)?;
+        if let Some(symbol) = tree.walk(bit) {
+            return Ok(symbol);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{distance_code, length_code, DeflateBlock, DeflateError};
+    use crate::bitstream::BitStream;
+
+    #[test]
+    fn test_length_code_boundaries() {
+        assert_eq!(length_code(3), (257, 0, 0));
+        assert_eq!(length_code(10), (264, 0, 0));
+        assert_eq!(length_code(11), (265, 1, 0));
+        assert_eq!(length_code(258), (285, 0, 0));
+    }
+
+    #[test]
+    fn test_distance_code_boundaries() {
+        assert_eq!(distance_code(1), (0, 0, 0));
+        assert_eq!(distance_code(4), (3, 0, 0));
+        assert_eq!(distance_code(5), (4, 1, 0));
+        assert_eq!(distance_code(32768), (29, 13, 8191));
+    }
+
+    #[test]
+    fn test_build_rejects_reserved_block_type() {
+        let mut stream = BitStream::new();
+        stream.push_lsb(1, 1); // BFINAL
+        stream.push_lsb(0b11, 2); // BTYPE (reserved)
+
+        let buffer = stream.into_bytes();
+        assert_eq!(
+            DeflateBlock::build(&buffer).unwrap_err(),
+            DeflateError::ReservedBlockType
+        );
+    }
+
+    #[test]
+    fn test_build_rejects_truncated_block_header() {
+        // A single bit is nowhere near enough to read BFINAL + BTYPE.
+        let buffer: &[u8] = &[];
+        assert_eq!(
+            DeflateBlock::build(buffer).unwrap_err(),
+            DeflateError::UnexpectedEnd
+        );
+    }
+
+    #[test]
+    fn test_build_rejects_mismatched_stored_length() {
+        let mut stream = BitStream::new();
+        stream.push_lsb(1, 1); // BFINAL
+        stream.push_lsb(0b00, 2); // BTYPE (stored)
+        stream.align_push_to_byte();
+        stream.push_lsb(5, 16); // LEN
+        stream.push_lsb(5, 16); // NLEN, should be !LEN
+
+        let buffer = stream.into_bytes();
+        assert_eq!(
+            DeflateBlock::build(&buffer).unwrap_err(),
+            DeflateError::BadStoredLength
+        );
+    }
+
+    #[test]
+    fn test_decompress_rejects_out_of_range_distance() {
+        // A fixed block whose first symbol is a back-reference can never
+        // be valid: there is nothing decompressed yet to point into.
+        let mut stream = BitStream::new();
+        stream.push_lsb(1, 1); // BFINAL
+        stream.push_lsb(0b01, 2); // BTYPE (fixed)
+
+        // Literal/length code 257 (shortest match, length 3) is 7 bits:
+        // 0000_000 in the fixed table (codes 256-279 are 7 bits, value =
+        // symbol - 256).
+        stream.push(257 - 256, 7);
+        // Distance code 0 (distance 1) is 5 bits.
+        stream.push(0, 5);
+        // End-of-block, literal/length code 256.
+        stream.push(256 - 256, 7);
+
+        let buffer = stream.into_bytes();
+        let block = DeflateBlock::build(&buffer).unwrap();
+        assert_eq!(block.decompress(), Err(DeflateError::InvalidDistance));
+    }
+}
@@ -0,0 +1,365 @@
+use crate::bitstream::BitStream;
+use crate::crc::crc32;
+use crate::deflate::{distance_code, length_code, CODE_LENGTH_ORDER};
+use crate::gzip::GzipHeader;
+use crate::lz77::{LzMatcher, LzOptions, Token};
+use crate::prefix::{PrefixTree, FIXED_CODE_LENGTHS, FIXED_DISTANCE_LENGTHS};
+
+/// Which Huffman strategy a DEFLATE block should be written with, mirroring
+/// 'deflate::BlockType' on the decode side.
+///
+/// # Variants
+///
+/// * 'Stored' - Emit the data verbatim in a type-0 block. No compression,
+///         but also no chance of expansion beyond 5 bytes of overhead.
+/// * 'Fixed' - Huffman-code the data against DEFLATE's predefined table,
+///         'prefix::FIXED_CODE_LENGTHS'. Cheap, no table to transmit.
+/// * 'Dynamic' - Build a Huffman table tailored to this block's own
+///         symbol frequencies and transmit it inline. Best ratio, most
+///         overhead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeflateMode {
+    Stored,
+    Fixed,
+    Dynamic,
+}
+
+/// Encodes raw bytes into a DEFLATE stream under a chosen 'DeflateMode'.
+///
+/// # Fields
+///
+/// * 'mode' - The block strategy to encode with.
+/// * 'lz_options' - The LZ77 match finder's probe depth and lazy-match
+///         behavior, used by 'Fixed' and 'Dynamic' blocks to find
+///         length/distance matches before Huffman coding the result.
+#[derive(Debug, Clone, Copy)]
+pub struct DeflateEncoder {
+    pub mode: DeflateMode,
+    pub lz_options: LzOptions,
+}
+
+impl DeflateEncoder {
+    /// Creates an encoder that will write blocks using 'mode', with the
+    /// default LZ77 tuning.
+    pub fn new(mode: DeflateMode) -> Self {
+        Self::with_lz_options(mode, LzOptions::default())
+    }
+
+    /// Creates an encoder that will write blocks using 'mode', searching
+    /// for matches with 'lz_options'.
+    pub fn with_lz_options(mode: DeflateMode, lz_options: LzOptions) -> Self {
+        Self { mode, lz_options }
+    }
+
+    /// Encodes 'data' as a single, final DEFLATE block.
+    ///
+    /// # Arguments
+    ///
+    /// * 'data' - The raw bytes to compress.
+    ///
+    /// # Returns
+    ///
+    /// The DEFLATE-compressed byte stream.
+    pub fn encode(&self, data: &[u8]) -> Vec<u8> {
+        encode_block(self.mode, self.lz_options, data, true).into_bytes()
+    }
+}
+
+/// Encodes 'data' as a single DEFLATE block under 'mode', setting BFINAL
+/// according to 'is_final'. Broken out of 'DeflateEncoder::encode' so
+/// 'parallel::ParallelEncoder' can build several independent blocks as
+/// bitstreams and concatenate them without forcing a byte boundary
+/// between blocks.
+///
+/// # Arguments
+///
+/// * 'mode' - The block strategy to encode with.
+/// * 'lz_options' - The LZ77 match finder's probe depth and lazy-match
+///         behavior, used by 'Fixed' and 'Dynamic' blocks.
+/// * 'data' - The raw bytes to compress into this block.
+/// * 'is_final' - Whether this is the last block in the stream (BFINAL).
+pub(crate) fn encode_block(
+    mode: DeflateMode,
+    lz_options: LzOptions,
+    data: &[u8],
+    is_final: bool,
+) -> BitStream {
+    let mut stream = BitStream::new();
+
+    match mode {
+        DeflateMode::Stored => encode_stored(&mut stream, data, is_final),
+        DeflateMode::Fixed => encode_fixed(&mut stream, data, lz_options, is_final),
+        DeflateMode::Dynamic => encode_dynamic(&mut stream, data, lz_options, is_final),
+    }
+
+    stream
+}
+
+/// A stored block's LEN/NLEN fields are 16 bits wide, so no single block
+/// can carry more than this many bytes (RFC 1951 section 3.2.4).
+const MAX_STORED_BLOCK_LEN: usize = 0xffff;
+
+fn encode_stored(stream: &mut BitStream, data: &[u8], is_final: bool) {
+    // Split into MAX_STORED_BLOCK_LEN-sized sub-blocks, since a single
+    // stored block's LEN/NLEN can't represent anything longer; a
+    // zero-length input still runs the loop once, writing one empty
+    // block so the stream has somewhere to carry BFINAL.
+    let mut offset = 0;
+    loop {
+        let end = (offset + MAX_STORED_BLOCK_LEN).min(data.len());
+        let chunk = &data[offset..end];
+        let block_final = is_final && end == data.len();
+
+        stream.push_lsb(block_final as u32, 1); // BFINAL
+        stream.push_lsb(0b00, 2); // BTYPE
+        stream.align_push_to_byte();
+
+        let len = chunk.len() as u32;
+        stream.push_lsb(len & 0xffff, 16); // LEN
+        stream.push_lsb(!len & 0xffff, 16); // NLEN, one's complement of LEN
+
+        for &byte in chunk {
+            stream.push_lsb(byte as u32, 8);
+        }
+
+        offset = end;
+        if offset == data.len() {
+            break;
+        }
+    }
+}
+
+fn encode_fixed(stream: &mut BitStream, data: &[u8], lz_options: LzOptions, is_final: bool) {
+    stream.push_lsb(is_final as u32, 1); // BFINAL
+    stream.push_lsb(0b01, 2); // BTYPE
+
+    let literal_tree = PrefixTree::from_lengths(&FIXED_CODE_LENGTHS);
+    let literal_table = code_table(&literal_tree, FIXED_CODE_LENGTHS.len());
+
+    let distance_tree = PrefixTree::from_lengths(&FIXED_DISTANCE_LENGTHS);
+    let distance_table = code_table(&distance_tree, FIXED_DISTANCE_LENGTHS.len());
+
+    let tokens = LzMatcher::new(lz_options).tokenize(data);
+    write_tokens(stream, &tokens, &literal_table, &distance_table);
+
+    let end_of_block = &literal_table[256];
+    stream.push(end_of_block.buffer, end_of_block.length);
+}
+
+fn encode_dynamic(stream: &mut BitStream, data: &[u8], lz_options: LzOptions, is_final: bool) {
+    stream.push_lsb(is_final as u32, 1); // BFINAL
+    stream.push_lsb(0b10, 2); // BTYPE
+
+    let tokens = LzMatcher::new(lz_options).tokenize(data);
+
+    let mut literal_frequencies = [0u64; 286];
+    let mut distance_frequencies = [0u64; 30];
+    for &token in &tokens {
+        match token {
+            Token::Literal(byte) => literal_frequencies[byte as usize] += 1,
+            Token::Match { length, distance } => {
+                let (length_symbol, _, _) = length_code(length);
+                literal_frequencies[length_symbol as usize] += 1;
+                let (distance_symbol, _, _) = distance_code(distance);
+                distance_frequencies[distance_symbol as usize] += 1;
+            }
+        }
+    }
+    literal_frequencies[256] = 1; // end-of-block, always present
+
+    // DEFLATE caps Huffman codes at 15 bits (RFC 1951 section 3.2.7).
+    let literal_tree = PrefixTree::from_frequencies(&literal_frequencies, 15);
+    let literal_lengths = code_lengths(&literal_tree, literal_frequencies.len());
+    let literal_table = code_table(&literal_tree, literal_lengths.len());
+
+    let distance_tree = PrefixTree::from_frequencies(&distance_frequencies, 15);
+    let distance_lengths = code_lengths(&distance_tree, distance_frequencies.len());
+    let distance_table = code_table(&distance_tree, distance_lengths.len());
+
+    write_dynamic_header(stream, &literal_lengths, &distance_lengths);
+    write_tokens(stream, &tokens, &literal_table, &distance_table);
+
+    let end_of_block = &literal_table[256];
+    stream.push(end_of_block.buffer, end_of_block.length);
+}
+
+// Writes a sequence of LZ77 tokens as Huffman-coded DEFLATE symbols:
+// literals and end-of-block come from 'literal_table', match lengths
+// from 'literal_table' at their 257-285 code, and match distances from
+// 'distance_table', each followed by whatever extra bits the symbol's
+// table entry calls for.
+fn write_tokens(
+    stream: &mut BitStream,
+    tokens: &[Token],
+    literal_table: &[crate::prefix::Code],
+    distance_table: &[crate::prefix::Code],
+) {
+    for &token in tokens {
+        match token {
+            Token::Literal(byte) => {
+                let code = &literal_table[byte as usize];
+                stream.push(code.buffer, code.length);
+            }
+            Token::Match { length, distance } => {
+                let (length_symbol, length_extra_bits, length_extra) = length_code(length);
+                let code = &literal_table[length_symbol as usize];
+                stream.push(code.buffer, code.length);
+                if length_extra_bits > 0 {
+                    stream.push_lsb(length_extra as u32, length_extra_bits);
+                }
+
+                let (distance_symbol, distance_extra_bits, distance_extra) =
+                    distance_code(distance);
+                let code = &distance_table[distance_symbol as usize];
+                stream.push(code.buffer, code.length);
+                if distance_extra_bits > 0 {
+                    stream.push_lsb(distance_extra as u32, distance_extra_bits);
+                }
+            }
+        }
+    }
+}
+
+fn write_dynamic_header(stream: &mut BitStream, literal_lengths: &[u8], distance_lengths: &[u8]) {
+    let hlit = literal_lengths.len() - 257;
+    let hdist = distance_lengths.len() - 1;
+
+    let mut combined = Vec::with_capacity(literal_lengths.len() + distance_lengths.len());
+    combined.extend_from_slice(literal_lengths);
+    combined.extend_from_slice(distance_lengths);
+
+    let mut code_length_frequencies = [0u64; 19];
+    for &length in &combined {
+        code_length_frequencies[length as usize] += 1;
+    }
+    // The code-length alphabet's own codes are transmitted as 3-bit
+    // fields, so they are capped at 7 bits (RFC 1951 section 3.2.7).
+    let code_length_tree = PrefixTree::from_frequencies(&code_length_frequencies, 7);
+    let code_length_lengths = code_lengths(&code_length_tree, 19);
+    let code_length_table = code_table(&code_length_tree, 19);
+
+    let hclen = CODE_LENGTH_ORDER
+        .iter()
+        .rposition(|&symbol| code_length_lengths[symbol] != 0)
+        .map(|idx| idx + 1)
+        .unwrap_or(4)
+        .max(4);
+
+    stream.push_lsb(hlit as u32, 5);
+    stream.push_lsb(hdist as u32, 5);
+    stream.push_lsb((hclen - 4) as u32, 4);
+
+    for &symbol in CODE_LENGTH_ORDER.iter().take(hclen) {
+        stream.push_lsb(code_length_lengths[symbol] as u32, 3);
+    }
+
+    for &length in &combined {
+        let code = &code_length_table[length as usize];
+        stream.push(code.buffer, code.length);
+    }
+}
+
+/// Builds a symbol -> Code lookup table out of a PrefixTree's leaves, for
+/// encoders that need to look up the canonical code for a symbol rather
+/// than walk the tree to decode one. 'alphabet_size' is the number of
+/// symbols the tree was built from ('tree.leaves' only holds the symbols
+/// that actually received a code, which can be fewer).
+fn code_table(tree: &PrefixTree, alphabet_size: usize) -> Vec<crate::prefix::Code> {
+    let mut table = vec![crate::prefix::Code::new(); alphabet_size];
+    for leaf in &tree.leaves {
+        if let Some(symbol) = leaf.value {
+            table[symbol] = leaf.code.clone();
+        }
+    }
+    table
+}
+
+/// Reads the per-symbol code lengths back out of a PrefixTree's leaves,
+/// for writing into a dynamic block's header.
+fn code_lengths(tree: &PrefixTree, alphabet_size: usize) -> Vec<u8> {
+    let mut lengths = vec![0u8; alphabet_size];
+    for leaf in &tree.leaves {
+        if let Some(symbol) = leaf.value {
+            lengths[symbol] = leaf.code.length;
+        }
+    }
+    lengths
+}
+
+/// Wraps a DEFLATE stream in a minimal gzip header and trailer, producing
+/// a complete, self-contained gzip member.
+///
+/// # Fields
+///
+/// * 'mode' - The DEFLATE block strategy to compress the payload with.
+#[derive(Debug, Clone, Copy)]
+pub struct GzipWriter {
+    pub mode: DeflateMode,
+}
+
+impl GzipWriter {
+    /// Creates a writer that will compress with 'mode'.
+    pub fn new(mode: DeflateMode) -> Self {
+        Self { mode }
+    }
+
+    /// Compresses 'data' into a full gzip member: header, DEFLATE payload,
+    /// and a CRC32 + ISIZE trailer.
+    ///
+    /// # Arguments
+    ///
+    /// * 'data' - The raw bytes to compress.
+    pub fn build(&self, data: &[u8]) -> Vec<u8> {
+        let mut output = GzipHeader::new().to_bytes();
+        output.extend(DeflateEncoder::new(self.mode).encode(data));
+        output.extend_from_slice(&crc32(data).to_le_bytes());
+        output.extend_from_slice(&(data.len() as u32).to_le_bytes());
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeflateEncoder, DeflateMode};
+    use crate::deflate::DeflateBlock;
+
+    fn round_trip(mode: DeflateMode, data: &[u8]) -> Vec<u8> {
+        let compressed = DeflateEncoder::new(mode).encode(data);
+        DeflateBlock::build(&compressed).unwrap().decompress().unwrap()
+    }
+
+    #[test]
+    fn test_stored_round_trips() {
+        let data = b"Lorem ipsum dolor sit amet".to_vec();
+        assert_eq!(round_trip(DeflateMode::Stored, &data), data);
+    }
+
+    #[test]
+    fn test_stored_round_trips_past_max_block_length() {
+        // One byte over the 16-bit LEN/NLEN limit, so 'encode_stored' must
+        // split this into more than one stored sub-block.
+        let data: Vec<u8> = (0..(super::MAX_STORED_BLOCK_LEN + 1))
+            .map(|i| (i % 256) as u8)
+            .collect();
+
+        assert_eq!(round_trip(DeflateMode::Stored, &data), data);
+    }
+
+    #[test]
+    fn test_stored_round_trips_empty_input() {
+        assert_eq!(round_trip(DeflateMode::Stored, &[]), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_fixed_round_trips() {
+        let data = b"the quick brown fox jumps over the lazy dog ".repeat(20);
+        assert_eq!(round_trip(DeflateMode::Fixed, &data), data);
+    }
+
+    #[test]
+    fn test_dynamic_round_trips() {
+        let data = b"the quick brown fox jumps over the lazy dog ".repeat(20);
+        assert_eq!(round_trip(DeflateMode::Dynamic, &data), data);
+    }
+}
@@ -0,0 +1,427 @@
+use std::fmt::Display;
+
+use crate::crc::crc32;
+use crate::deflate::DeflateBlock;
+
+/// Errors that can occur while parsing or decompressing a gzip member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GzipError {
+    /// The buffer is shorter than a minimal gzip header, or ends before
+    /// a member's 8-byte CRC32 + ISIZE trailer.
+    TooShort,
+    /// The first two bytes were not the gzip magic number (0x1f, 0x8b).
+    BadMagic,
+    /// The compression method byte was not 8 (deflate).
+    UnsupportedMethod(u8),
+    /// Bubbled up from the underlying DEFLATE stream.
+    Deflate(crate::deflate::DeflateError),
+    /// The decompressed data's CRC32 did not match the trailer.
+    CrcMismatch,
+    /// The decompressed data's length, mod 2^32, did not match the
+    /// trailer's ISIZE field.
+    SizeMismatch,
+}
+
+impl Display for GzipError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GzipError::TooShort => write!(f, "buffer is too short to contain a gzip header"),
+            GzipError::BadMagic => write!(f, "missing gzip magic number (0x1f 0x8b)"),
+            GzipError::UnsupportedMethod(m) => {
+                write!(f, "unsupported compression method: {m}")
+            }
+            GzipError::Deflate(e) => write!(f, "{e}"),
+            GzipError::CrcMismatch => write!(f, "decompressed data failed its CRC32 check"),
+            GzipError::SizeMismatch => {
+                write!(f, "decompressed data did not match the trailer's ISIZE")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GzipError {}
+
+impl From<crate::deflate::DeflateError> for GzipError {
+    fn from(value: crate::deflate::DeflateError) -> Self {
+        GzipError::Deflate(value)
+    }
+}
+
+/// The flag bits stored in a gzip header's FLG byte.
+///
+/// # Fields
+///
+/// * 'ftext' - Hint that the file contents are probably ASCII text.
+/// * 'fhcrc' - A CRC16 of the header is present immediately before the
+///         deflate stream.
+/// * 'fextra' - An FEXTRA field is present.
+/// * 'fname' - A null-terminated original file name is present.
+/// * 'fcomment' - A null-terminated comment is present.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GzipFlags {
+    pub ftext: bool,
+    pub fhcrc: bool,
+    pub fextra: bool,
+    pub fname: bool,
+    pub fcomment: bool,
+}
+
+impl GzipFlags {
+    fn from_byte(byte: u8) -> Self {
+        Self {
+            ftext: byte & 0b0000_0001 != 0,
+            fhcrc: byte & 0b0000_0010 != 0,
+            fextra: byte & 0b0000_0100 != 0,
+            fname: byte & 0b0000_1000 != 0,
+            fcomment: byte & 0b0001_0000 != 0,
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        let mut byte = 0u8;
+        if self.ftext {
+            byte |= 0b0000_0001;
+        }
+        if self.fhcrc {
+            byte |= 0b0000_0010;
+        }
+        if self.fextra {
+            byte |= 0b0000_0100;
+        }
+        if self.fname {
+            byte |= 0b0000_1000;
+        }
+        if self.fcomment {
+            byte |= 0b0001_0000;
+        }
+        byte
+    }
+}
+
+/// A parsed gzip member header, as laid out in RFC 1952 section 2.3.
+///
+/// # Fields
+///
+/// * 'cm' - The compression method, 8 for deflate.
+/// * 'flg' - The parsed flag bits.
+/// * 'mtime' - Modification time, seconds since the Unix epoch, or 0.
+/// * 'xfl' - Extra flags describing the compression effort used.
+/// * 'os' - The identifier of the OS the file was compressed on.
+/// * 'crc' - The header CRC16, present only when 'flg.fhcrc' is set.
+/// * 'fextra' - The raw FEXTRA payload, present only when 'flg.fextra' is
+///         set.
+/// * 'fname' - The original file name, present only when 'flg.fname' is
+///         set.
+/// * 'fcomment' - A free-text comment, present only when 'flg.fcomment' is
+///         set.
+/// * 'end_idx' - The index into the source buffer immediately after the
+///         header, i.e. where the deflate stream begins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GzipHeader {
+    pub cm: u8,
+    pub flg: GzipFlags,
+    pub mtime: u32,
+    pub xfl: u8,
+    pub os: u8,
+    pub crc: Option<u16>,
+    pub fextra: Option<Vec<u8>>,
+    pub fname: Option<String>,
+    pub fcomment: Option<String>,
+    pub end_idx: usize,
+}
+
+impl Default for GzipHeader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GzipHeader {
+    /// Builds a minimal header for a freshly compressed deflate member:
+    /// no FEXTRA/FNAME/FCOMMENT, no header CRC, and an unset mtime.
+    pub fn new() -> Self {
+        Self {
+            cm: 8,
+            flg: GzipFlags::default(),
+            mtime: 0,
+            xfl: 0,
+            os: 255, // "unknown", per RFC 1952 section 2.3.1.
+            crc: None,
+            fextra: None,
+            fname: None,
+            fcomment: None,
+            end_idx: 0,
+        }
+    }
+
+    fn parse(buffer: &[u8]) -> Result<Self, GzipError> {
+        if buffer.len() < 10 {
+            return Err(GzipError::TooShort);
+        }
+        if buffer[0] != 0x1f || buffer[1] != 0x8b {
+            return Err(GzipError::BadMagic);
+        }
+
+        let cm = buffer[2];
+        if cm != 8 {
+            return Err(GzipError::UnsupportedMethod(cm));
+        }
+
+        let flg = GzipFlags::from_byte(buffer[3]);
+        let mtime = u32::from_le_bytes(buffer[4..8].try_into().unwrap());
+        let xfl = buffer[8];
+        let os = buffer[9];
+
+        let mut idx = 10;
+
+        let fextra = if flg.fextra {
+            if idx + 2 > buffer.len() {
+                return Err(GzipError::TooShort);
+            }
+            let len = u16::from_le_bytes(buffer[idx..idx + 2].try_into().unwrap()) as usize;
+            idx += 2;
+            if idx + len > buffer.len() {
+                return Err(GzipError::TooShort);
+            }
+            let data = buffer[idx..idx + len].to_vec();
+            idx += len;
+            Some(data)
+        } else {
+            None
+        };
+
+        let fname = if flg.fname {
+            let end = buffer[idx..]
+                .iter()
+                .position(|&b| b == 0)
+                .map(|p| idx + p)
+                .ok_or(GzipError::TooShort)?;
+            let name = String::from_utf8_lossy(&buffer[idx..end]).into_owned();
+            idx = end + 1;
+            Some(name)
+        } else {
+            None
+        };
+
+        let fcomment = if flg.fcomment {
+            let end = buffer[idx..]
+                .iter()
+                .position(|&b| b == 0)
+                .map(|p| idx + p)
+                .ok_or(GzipError::TooShort)?;
+            let comment = String::from_utf8_lossy(&buffer[idx..end]).into_owned();
+            idx = end + 1;
+            Some(comment)
+        } else {
+            None
+        };
+
+        let crc = if flg.fhcrc {
+            if idx + 2 > buffer.len() {
+                return Err(GzipError::TooShort);
+            }
+            let value = u16::from_le_bytes(buffer[idx..idx + 2].try_into().unwrap());
+            idx += 2;
+            Some(value)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            cm,
+            flg,
+            mtime,
+            xfl,
+            os,
+            crc,
+            fextra,
+            fname,
+            fcomment,
+            end_idx: idx,
+        })
+    }
+
+    /// Serializes the header back into its on-disk byte representation.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0x1f, 0x8b, self.cm, self.flg.to_byte()];
+        bytes.extend_from_slice(&self.mtime.to_le_bytes());
+        bytes.push(self.xfl);
+        bytes.push(self.os);
+
+        if let Some(fextra) = &self.fextra {
+            bytes.extend_from_slice(&(fextra.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(fextra);
+        }
+        if let Some(fname) = &self.fname {
+            bytes.extend_from_slice(fname.as_bytes());
+            bytes.push(0);
+        }
+        if let Some(fcomment) = &self.fcomment {
+            bytes.extend_from_slice(fcomment.as_bytes());
+            bytes.push(0);
+        }
+        if let Some(crc) = self.crc {
+            bytes.extend_from_slice(&crc.to_le_bytes());
+        }
+
+        bytes
+    }
+}
+
+/// A single parsed gzip member: a header plus the raw deflate stream (and
+/// trailing CRC32/ISIZE) that followed it.
+///
+/// # Fields
+///
+/// * 'header' - The parsed gzip header.
+/// * 'deflate' - The bytes following the header that make up the deflate
+///         stream itself, not including this member's trailer.
+/// * 'end_idx' - The index into the source buffer immediately after this
+///         member's trailer, i.e. where the next member (if any) begins
+///         in a concatenated gzip stream.
+#[derive(Debug, Clone)]
+pub struct GzipFile {
+    pub header: GzipHeader,
+    pub deflate: Vec<u8>,
+    block: DeflateBlock,
+    crc: u32,
+    isize: u32,
+    pub end_idx: usize,
+}
+
+impl GzipFile {
+    /// Parses a single gzip member out of the front of 'buffer': a
+    /// header, its deflate stream, and a CRC32 + ISIZE trailer. Bytes
+    /// beyond 'end_idx' (such as a following member) are left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * 'buffer' - The raw bytes of a gzip file, starting at a member
+    ///         boundary.
+    ///
+    /// # Returns
+    ///
+    /// A GzipFile on success, or a GzipError describing why the buffer
+    /// could not be parsed.
+    pub fn build(buffer: &[u8]) -> Result<Self, GzipError> {
+        let header = GzipHeader::parse(buffer)?;
+        let (block, consumed) = DeflateBlock::build_prefix(&buffer[header.end_idx..])?;
+        let deflate_end = header.end_idx + consumed;
+
+        if buffer.len() < deflate_end + 8 {
+            return Err(GzipError::TooShort);
+        }
+        let deflate = buffer[header.end_idx..deflate_end].to_vec();
+        let crc = u32::from_le_bytes(buffer[deflate_end..deflate_end + 4].try_into().unwrap());
+        let isize = u32::from_le_bytes(
+            buffer[deflate_end + 4..deflate_end + 8]
+                .try_into()
+                .unwrap(),
+        );
+
+        Ok(Self {
+            header,
+            deflate,
+            block,
+            crc,
+            isize,
+            end_idx: deflate_end + 8,
+        })
+    }
+
+    /// Decompresses every back-to-back gzip member in 'buffer' (RFC 1952
+    /// section 2.2 allows concatenation) and returns their decompressed
+    /// output concatenated in turn, after verifying each member's
+    /// trailer.
+    ///
+    /// # Arguments
+    ///
+    /// * 'buffer' - The raw bytes of one or more concatenated gzip
+    ///         members.
+    pub fn decompress_stream(buffer: &[u8]) -> Result<Vec<u8>, GzipError> {
+        let mut output = Vec::new();
+        let mut offset = 0;
+
+        while offset < buffer.len() {
+            let file = Self::build(&buffer[offset..])?;
+            output.extend(file.decompress()?);
+            offset += file.end_idx;
+        }
+
+        Ok(output)
+    }
+
+    /// Decompresses this member's deflate stream, verifying the result
+    /// against the trailer's CRC32 and ISIZE fields.
+    pub fn decompress(&self) -> Result<Vec<u8>, GzipError> {
+        let data = self.block.decompress()?;
+
+        if crc32(&data) != self.crc {
+            return Err(GzipError::CrcMismatch);
+        }
+        if data.len() as u32 != self.isize {
+            return Err(GzipError::SizeMismatch);
+        }
+
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GzipError, GzipFile};
+    use crate::encoder::{DeflateMode, GzipWriter};
+
+    #[test]
+    fn test_build_decompress_round_trips() {
+        let data = b"Lorem ipsum dolor sit amet".to_vec();
+        let bytes = GzipWriter::new(DeflateMode::Dynamic).build(&data);
+
+        let file = GzipFile::build(&bytes).unwrap();
+
+        assert_eq!(file.decompress().unwrap(), data);
+        assert_eq!(file.end_idx, bytes.len());
+    }
+
+    #[test]
+    fn test_decompress_detects_crc_mismatch() {
+        let data = b"Lorem ipsum dolor sit amet".to_vec();
+        let mut bytes = GzipWriter::new(DeflateMode::Dynamic).build(&data);
+
+        let crc_idx = bytes.len() - 8;
+        bytes[crc_idx] ^= 0xff;
+
+        let file = GzipFile::build(&bytes).unwrap();
+        assert_eq!(file.decompress(), Err(GzipError::CrcMismatch));
+    }
+
+    #[test]
+    fn test_decompress_stream_concatenates_members() {
+        let first = b"Lorem ipsum".to_vec();
+        let second = b"dolor sit amet".to_vec();
+
+        let mut bytes = GzipWriter::new(DeflateMode::Fixed).build(&first);
+        bytes.extend(GzipWriter::new(DeflateMode::Stored).build(&second));
+
+        let output = GzipFile::decompress_stream(&bytes).unwrap();
+
+        assert_eq!(output, [first, second].concat());
+    }
+
+    #[test]
+    fn test_build_rejects_unterminated_fname() {
+        // A minimal 10-byte header with FNAME set (FLG bit 0b0000_1000)
+        // but no trailing NUL to end the file name.
+        let bytes: Vec<u8> = vec![0x1f, 0x8b, 8, 0b0000_1000, 0, 0, 0, 0, 0, 0xff];
+
+        assert_eq!(GzipFile::build(&bytes).unwrap_err(), GzipError::TooShort);
+    }
+
+    #[test]
+    fn test_build_rejects_unterminated_fcomment() {
+        // Same, but with FCOMMENT set (FLG bit 0b0001_0000) instead.
+        let bytes: Vec<u8> = vec![0x1f, 0x8b, 8, 0b0001_0000, 0, 0, 0, 0, 0, 0xff];
+
+        assert_eq!(GzipFile::build(&bytes).unwrap_err(), GzipError::TooShort);
+    }
+}
@@ -0,0 +1,716 @@
+use std::collections::VecDeque;
+
+use crate::bitstream::BitStream;
+use crate::deflate::{DeflateError, CODE_LENGTH_ORDER, DISTANCE_TABLE, LENGTH_TABLE};
+use crate::prefix::{PrefixTree, FIXED_CODE_LENGTHS, FIXED_DISTANCE_LENGTHS};
+
+/// The sliding window size DEFLATE allows back-references into (RFC 1951
+/// section 2.2), and so the largest number of trailing decompressed
+/// bytes 'Inflate' needs to keep around.
+const WINDOW_SIZE: usize = 32768;
+
+/// What 'Inflate::decompress_data' accomplished during a single call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InflateStatus {
+    /// The bit stream ran out before a symbol, block header, or Huffman
+    /// table could be fully read. Feed more input and call again.
+    NeedInput,
+    /// The output buffer filled up before the stream did. Call again
+    /// with a fresh output buffer and 'repeat' set, without supplying
+    /// new input, to keep draining.
+    OutputFull,
+    /// The final block (BFINAL) has been fully decoded; there is
+    /// nothing left to produce.
+    Done,
+}
+
+/// The result of a single 'Inflate::decompress_data' call.
+///
+/// # Fields
+///
+/// * 'input_consumed' - How many bytes of the input slice passed in were
+///         absorbed into the bit stream (either all of it, or none if
+///         'repeat' was set).
+/// * 'output_written' - How many bytes of the output slice were filled
+///         in.
+/// * 'status' - Why the call stopped producing output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InflateProgress {
+    pub input_consumed: usize,
+    pub output_written: usize,
+    pub status: InflateStatus,
+}
+
+// Whether an 'advance' step produced a byte, needs more input before it
+// can produce one, or has nothing left to produce.
+enum Advance {
+    Byte(u8),
+    NeedInput,
+    Finished,
+}
+
+// The steps involved in reading a dynamic block's two Huffman tables
+// (RFC 1951 section 3.2.7), broken out so each one can suspend and
+// resume across calls without losing its place.
+enum DynamicHeaderState {
+    Hlit,
+    Hdist {
+        hlit: usize,
+    },
+    Hclen {
+        hlit: usize,
+        hdist: usize,
+    },
+    CodeLengthLengths {
+        hlit: usize,
+        hdist: usize,
+        hclen: usize,
+        lengths: [u8; 19],
+        index: usize,
+    },
+    MainLengths {
+        hlit: usize,
+        hdist: usize,
+        code_length_tree: PrefixTree,
+        lengths: Vec<u8>,
+    },
+    RepeatExtra {
+        hlit: usize,
+        hdist: usize,
+        code_length_tree: PrefixTree,
+        lengths: Vec<u8>,
+        symbol: usize,
+    },
+}
+
+// What one step of dynamic-header parsing accomplished.
+enum HeaderStep {
+    // Progress was made with no byte to show for it yet; resume at this
+    // state immediately, without waiting for more input.
+    Continue(DynamicHeaderState),
+    // Not enough bits were available to make progress; resume at this
+    // (unchanged) state once more input arrives.
+    Blocked(DynamicHeaderState),
+    // Both Huffman tables are built; 'self.phase' has already been set
+    // to 'Phase::Symbols'.
+    Done,
+}
+
+// Where decoding a single literal/length/distance symbol has gotten to,
+// so a partially-read symbol (or a partially-copied match) can resume
+// exactly where it left off on the next call.
+enum SymbolState {
+    Ready,
+    LengthExtra {
+        symbol: usize,
+    },
+    DistanceSymbol {
+        length: u16,
+    },
+    DistanceExtra {
+        length: u16,
+        distance_symbol: usize,
+    },
+    Emitting {
+        length: u16,
+        distance: u16,
+        emitted: u16,
+    },
+}
+
+// What one step of symbol decoding accomplished.
+enum SymbolStep {
+    // Progress was made with no byte to show for it yet; resume at this
+    // state immediately.
+    Continue(SymbolState),
+    // Not enough bits were available; resume at this (unchanged) state
+    // once more input arrives.
+    Blocked(SymbolState),
+    // A decompressed byte is ready; resume at this state next time.
+    Byte(u8, SymbolState),
+    // The end-of-block symbol was read; 'self.phase' has already been
+    // set to the next block's phase.
+    BlockDone,
+}
+
+// The overall block-level state: which part of a block is currently
+// being read.
+enum Phase {
+    BlockHeader,
+    StoredAlign,
+    StoredLen,
+    StoredNlen { len: u16 },
+    StoredData { remaining: u16 },
+    DynamicHeader(DynamicHeaderState),
+    Symbols(SymbolState),
+    Done,
+}
+
+/// An incremental DEFLATE decompressor that carries its bit-reader
+/// state, the current block's Huffman trees, and a persistent 32 KiB
+/// sliding window across calls to 'decompress_data', mirroring nihav's
+/// chunked inflate. This allows decoding streams too large to hold in
+/// memory at once, by feeding input in chunks and draining output into
+/// fixed-size buffers.
+///
+/// # Fields
+///
+/// * 'stream' - The bits fed in so far but not yet fully decoded.
+/// * 'window' - The last up to 'WINDOW_SIZE' decompressed bytes, used to
+///         resolve back-references; never exposed to callers directly.
+/// * 'phase' - Where in the current block decoding has gotten to.
+/// * 'is_final' - The BFINAL bit of the block currently being decoded.
+/// * 'literal_tree' - The current block's literal/length Huffman tree,
+///         present only while 'phase' is 'Symbols'.
+/// * 'distance_tree' - The current block's distance Huffman tree,
+///         present only while 'phase' is 'Symbols'.
+pub struct Inflate {
+    stream: BitStream,
+    window: VecDeque<u8>,
+    phase: Phase,
+    is_final: bool,
+    literal_tree: Option<PrefixTree>,
+    distance_tree: Option<PrefixTree>,
+}
+
+impl Default for Inflate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inflate {
+    /// Creates a new, empty incremental decompressor, ready to be fed
+    /// the start of a DEFLATE stream.
+    pub fn new() -> Self {
+        Self {
+            stream: BitStream::new(),
+            window: VecDeque::with_capacity(WINDOW_SIZE),
+            phase: Phase::BlockHeader,
+            is_final: false,
+            literal_tree: None,
+            distance_tree: None,
+        }
+    }
+
+    /// Feeds 'input' into the decompressor and drains as much
+    /// decompressed data into 'output' as will fit.
+    ///
+    /// # Arguments
+    ///
+    /// * 'input' - The next chunk of DEFLATE-compressed bytes. Ignored
+    ///         (and should typically be empty) when 'repeat' is true.
+    /// * 'output' - Where to write decompressed bytes.
+    /// * 'repeat' - When true, no new input is consumed; this call only
+    ///         continues draining output left over from a previous call
+    ///         that returned 'InflateStatus::OutputFull'.
+    ///
+    /// # Returns
+    ///
+    /// An 'InflateProgress' describing how much of 'input' was consumed,
+    /// how much of 'output' was filled, and why the call stopped.
+    pub fn decompress_data(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+        repeat: bool,
+    ) -> Result<InflateProgress, DeflateError> {
+        if !repeat {
+            self.stream.extend_bytes(input);
+        }
+
+        let mut written = 0;
+        let status = loop {
+            if written == output.len() {
+                break InflateStatus::OutputFull;
+            }
+
+            match self.advance()? {
+                Advance::Byte(byte) => {
+                    output[written] = byte;
+                    written += 1;
+                }
+                Advance::NeedInput => break InflateStatus::NeedInput,
+                Advance::Finished => break InflateStatus::Done,
+            }
+        };
+
+        Ok(InflateProgress {
+            input_consumed: if repeat { 0 } else { input.len() },
+            output_written: written,
+            status,
+        })
+    }
+
+    // Pushes 'byte' onto the sliding window, evicting the oldest byte
+    // once the window exceeds WINDOW_SIZE.
+    fn push_window(&mut self, byte: u8) {
+        self.window.push_back(byte);
+        if self.window.len() > WINDOW_SIZE {
+            self.window.pop_front();
+        }
+    }
+
+    // The phase to move to once the current block finishes: the next
+    // block's header, or done if this was the final block.
+    fn next_block_phase(&self) -> Phase {
+        if self.is_final {
+            Phase::Done
+        } else {
+            Phase::BlockHeader
+        }
+    }
+
+    // Advances the state machine until it produces a byte, determines it
+    // needs more input, or finishes the stream. Never consumes bits it
+    // isn't certain are available, so a call that returns NeedInput can
+    // be retried after more input arrives without losing any state.
+    fn advance(&mut self) -> Result<Advance, DeflateError> {
+        loop {
+            match std::mem::replace(&mut self.phase, Phase::Done) {
+                Phase::Done => return Ok(Advance::Finished),
+                Phase::BlockHeader => {
+                    if self.stream.len() < 3 {
+                        self.phase = Phase::BlockHeader;
+                        return Ok(Advance::NeedInput);
+                    }
+                    self.is_final = self.stream.pop().unwrap() == 1;
+                    let btype = self.stream.pop_bits(2).unwrap();
+
+                    self.phase = match btype {
+                        0b00 => Phase::StoredAlign,
+                        0b01 => {
+                            self.literal_tree = Some(PrefixTree::from_lengths(&FIXED_CODE_LENGTHS));
+                            self.distance_tree =
+                                Some(PrefixTree::from_lengths(&FIXED_DISTANCE_LENGTHS));
+                            Phase::Symbols(SymbolState::Ready)
+                        }
+                        0b10 => Phase::DynamicHeader(DynamicHeaderState::Hlit),
+                        _ => return Err(DeflateError::ReservedBlockType),
+                    };
+                }
+                Phase::StoredAlign => {
+                    let skip = self.stream.bits_until_byte_boundary();
+                    if self.stream.len() < skip as usize {
+                        self.phase = Phase::StoredAlign;
+                        return Ok(Advance::NeedInput);
+                    }
+                    for _ in 0..skip {
+                        self.stream.pop();
+                    }
+                    self.phase = Phase::StoredLen;
+                }
+                Phase::StoredLen => {
+                    if self.stream.len() < 16 {
+                        self.phase = Phase::StoredLen;
+                        return Ok(Advance::NeedInput);
+                    }
+                    let len = self.stream.pop_bits(16).unwrap() as u16;
+                    self.phase = Phase::StoredNlen { len };
+                }
+                Phase::StoredNlen { len } => {
+                    if self.stream.len() < 16 {
+                        self.phase = Phase::StoredNlen { len };
+                        return Ok(Advance::NeedInput);
+                    }
+                    let nlen = self.stream.pop_bits(16).unwrap() as u16;
+                    if len != !nlen {
+                        return Err(DeflateError::BadStoredLength);
+                    }
+                    self.phase = Phase::StoredData { remaining: len };
+                }
+                Phase::StoredData { remaining } => {
+                    if remaining == 0 {
+                        self.phase = self.next_block_phase();
+                        continue;
+                    }
+                    if self.stream.len() < 8 {
+                        self.phase = Phase::StoredData { remaining };
+                        return Ok(Advance::NeedInput);
+                    }
+                    let byte = self.stream.pop_bits(8).unwrap() as u8;
+                    self.push_window(byte);
+                    self.phase = Phase::StoredData {
+                        remaining: remaining - 1,
+                    };
+                    return Ok(Advance::Byte(byte));
+                }
+                Phase::DynamicHeader(state) => match self.step_dynamic_header(state)? {
+                    HeaderStep::Continue(next) => self.phase = Phase::DynamicHeader(next),
+                    HeaderStep::Blocked(next) => {
+                        self.phase = Phase::DynamicHeader(next);
+                        return Ok(Advance::NeedInput);
+                    }
+                    HeaderStep::Done => {} // self.phase already set to Symbols
+                },
+                Phase::Symbols(state) => match self.step_symbol(state)? {
+                    SymbolStep::Continue(next) => self.phase = Phase::Symbols(next),
+                    SymbolStep::Blocked(next) => {
+                        self.phase = Phase::Symbols(next);
+                        return Ok(Advance::NeedInput);
+                    }
+                    SymbolStep::Byte(byte, next) => {
+                        self.phase = Phase::Symbols(next);
+                        return Ok(Advance::Byte(byte));
+                    }
+                    SymbolStep::BlockDone => {} // self.phase already set
+                },
+            }
+        }
+    }
+
+    // Advances one step of dynamic-table parsing (RFC 1951 section
+    // 3.2.7): HLIT/HDIST/HCLEN, then the code-length alphabet's own
+    // lengths, then the literal/distance alphabets' lengths (which may
+    // use repeat codes 16-18 against the code-length alphabet).
+    fn step_dynamic_header(
+        &mut self,
+        state: DynamicHeaderState,
+    ) -> Result<HeaderStep, DeflateError> {
+        match state {
+            DynamicHeaderState::Hlit => {
+                if self.stream.len() < 5 {
+                    return Ok(HeaderStep::Blocked(DynamicHeaderState::Hlit));
+                }
+                let hlit = self.stream.pop_bits(5).unwrap() as usize + 257;
+                Ok(HeaderStep::Continue(DynamicHeaderState::Hdist { hlit }))
+            }
+            DynamicHeaderState::Hdist { hlit } => {
+                if self.stream.len() < 5 {
+                    return Ok(HeaderStep::Blocked(DynamicHeaderState::Hdist { hlit }));
+                }
+                let hdist = self.stream.pop_bits(5).unwrap() as usize + 1;
+                Ok(HeaderStep::Continue(DynamicHeaderState::Hclen {
+                    hlit,
+                    hdist,
+                }))
+            }
+            DynamicHeaderState::Hclen { hlit, hdist } => {
+                if self.stream.len() < 4 {
+                    return Ok(HeaderStep::Blocked(DynamicHeaderState::Hclen {
+                        hlit,
+                        hdist,
+                    }));
+                }
+                let hclen = self.stream.pop_bits(4).unwrap() as usize + 4;
+                Ok(HeaderStep::Continue(DynamicHeaderState::CodeLengthLengths {
+                    hlit,
+                    hdist,
+                    hclen,
+                    lengths: [0u8; 19],
+                    index: 0,
+                }))
+            }
+            DynamicHeaderState::CodeLengthLengths {
+                hlit,
+                hdist,
+                hclen,
+                mut lengths,
+                index,
+            } => {
+                if index == hclen {
+                    let code_length_tree = PrefixTree::from_lengths(&lengths);
+                    return Ok(HeaderStep::Continue(DynamicHeaderState::MainLengths {
+                        hlit,
+                        hdist,
+                        code_length_tree,
+                        lengths: Vec::with_capacity(hlit + hdist),
+                    }));
+                }
+                if self.stream.len() < 3 {
+                    return Ok(HeaderStep::Blocked(DynamicHeaderState::CodeLengthLengths {
+                        hlit,
+                        hdist,
+                        hclen,
+                        lengths,
+                        index,
+                    }));
+                }
+                lengths[CODE_LENGTH_ORDER[index]] = self.stream.pop_bits(3).unwrap() as u8;
+                Ok(HeaderStep::Continue(DynamicHeaderState::CodeLengthLengths {
+                    hlit,
+                    hdist,
+                    hclen,
+                    lengths,
+                    index: index + 1,
+                }))
+            }
+            DynamicHeaderState::MainLengths {
+                hlit,
+                hdist,
+                mut code_length_tree,
+                lengths,
+            } => {
+                if lengths.len() >= hlit + hdist {
+                    self.literal_tree = Some(PrefixTree::from_lengths(&lengths[..hlit]));
+                    self.distance_tree =
+                        Some(PrefixTree::from_lengths(&lengths[hlit..hlit + hdist]));
+                    self.phase = Phase::Symbols(SymbolState::Ready);
+                    return Ok(HeaderStep::Done);
+                }
+                if self.stream.is_empty() {
+                    return Ok(HeaderStep::Blocked(DynamicHeaderState::MainLengths {
+                        hlit,
+                        hdist,
+                        code_length_tree,
+                        lengths,
+                    }));
+                }
+                let bit = self.stream.pop().unwrap();
+                match code_length_tree.walk(bit) {
+                    None => Ok(HeaderStep::Continue(DynamicHeaderState::MainLengths {
+                        hlit,
+                        hdist,
+                        code_length_tree,
+                        lengths,
+                    })),
+                    Some(symbol @ 0..=15) => {
+                        let mut lengths = lengths;
+                        lengths.push(symbol as u8);
+                        Ok(HeaderStep::Continue(DynamicHeaderState::MainLengths {
+                            hlit,
+                            hdist,
+                            code_length_tree,
+                            lengths,
+                        }))
+                    }
+                    Some(symbol @ 16..=18) => {
+                        Ok(HeaderStep::Continue(DynamicHeaderState::RepeatExtra {
+                            hlit,
+                            hdist,
+                            code_length_tree,
+                            lengths,
+                            symbol,
+                        }))
+                    }
+                    Some(_) => Err(DeflateError::InvalidCode),
+                }
+            }
+            DynamicHeaderState::RepeatExtra {
+                hlit,
+                hdist,
+                code_length_tree,
+                mut lengths,
+                symbol,
+            } => {
+                let extra_bits = match symbol {
+                    16 => 2,
+                    17 => 3,
+                    18 => 7,
+                    _ => unreachable!("RepeatExtra only ever holds symbols 16-18"),
+                };
+                if self.stream.len() < extra_bits {
+                    return Ok(HeaderStep::Blocked(DynamicHeaderState::RepeatExtra {
+                        hlit,
+                        hdist,
+                        code_length_tree,
+                        lengths,
+                        symbol,
+                    }));
+                }
+
+                match symbol {
+                    16 => {
+                        let repeat = self.stream.pop_bits(2).unwrap() + 3;
+                        let previous = *lengths.last().ok_or(DeflateError::InvalidCode)?;
+                        lengths.extend(std::iter::repeat_n(previous, repeat as usize));
+                    }
+                    17 => {
+                        let repeat = self.stream.pop_bits(3).unwrap() + 3;
+                        lengths.extend(std::iter::repeat_n(0u8, repeat as usize));
+                    }
+                    18 => {
+                        let repeat = self.stream.pop_bits(7).unwrap() + 11;
+                        lengths.extend(std::iter::repeat_n(0u8, repeat as usize));
+                    }
+                    _ => unreachable!("RepeatExtra only ever holds symbols 16-18"),
+                }
+
+                Ok(HeaderStep::Continue(DynamicHeaderState::MainLengths {
+                    hlit,
+                    hdist,
+                    code_length_tree,
+                    lengths,
+                }))
+            }
+        }
+    }
+
+    // Advances one step of literal/length/distance symbol decoding (RFC
+    // 1951 section 3.2.5), including walking the Huffman trees one bit
+    // at a time so a partially-read symbol can resume mid-code, and
+    // copying one back-reference byte at a time so a partially-copied
+    // match can resume mid-copy.
+    fn step_symbol(&mut self, state: SymbolState) -> Result<SymbolStep, DeflateError> {
+        match state {
+            SymbolState::Ready => {
+                if self.stream.is_empty() {
+                    return Ok(SymbolStep::Blocked(SymbolState::Ready));
+                }
+                let bit = self.stream.pop().unwrap();
+                let symbol = self.literal_tree.as_mut().unwrap().walk(bit);
+                match symbol {
+                    None => Ok(SymbolStep::Continue(SymbolState::Ready)),
+                    Some(symbol @ 0..=255) => {
+                        let byte = symbol as u8;
+                        self.push_window(byte);
+                        Ok(SymbolStep::Byte(byte, SymbolState::Ready))
+                    }
+                    Some(256) => {
+                        self.literal_tree = None;
+                        self.distance_tree = None;
+                        self.phase = self.next_block_phase();
+                        Ok(SymbolStep::BlockDone)
+                    }
+                    Some(symbol @ 257..=285) => {
+                        Ok(SymbolStep::Continue(SymbolState::LengthExtra { symbol }))
+                    }
+                    Some(_) => Err(DeflateError::InvalidCode),
+                }
+            }
+            SymbolState::LengthExtra { symbol } => {
+                let (base, extra_bits) = LENGTH_TABLE[symbol - 257];
+                if self.stream.len() < extra_bits as usize {
+                    return Ok(SymbolStep::Blocked(SymbolState::LengthExtra { symbol }));
+                }
+                let extra = if extra_bits > 0 {
+                    self.stream.pop_bits(extra_bits).unwrap() as u16
+                } else {
+                    0
+                };
+                Ok(SymbolStep::Continue(SymbolState::DistanceSymbol {
+                    length: base + extra,
+                }))
+            }
+            SymbolState::DistanceSymbol { length } => {
+                if self.stream.is_empty() {
+                    return Ok(SymbolStep::Blocked(SymbolState::DistanceSymbol { length }));
+                }
+                let bit = self.stream.pop().unwrap();
+                match self.distance_tree.as_mut().unwrap().walk(bit) {
+                    None => Ok(SymbolStep::Continue(SymbolState::DistanceSymbol { length })),
+                    Some(distance_symbol) if distance_symbol < DISTANCE_TABLE.len() => {
+                        Ok(SymbolStep::Continue(SymbolState::DistanceExtra {
+                            length,
+                            distance_symbol,
+                        }))
+                    }
+                    Some(_) => Err(DeflateError::InvalidCode),
+                }
+            }
+            SymbolState::DistanceExtra {
+                length,
+                distance_symbol,
+            } => {
+                let (base, extra_bits) = DISTANCE_TABLE[distance_symbol];
+                if self.stream.len() < extra_bits as usize {
+                    return Ok(SymbolStep::Blocked(SymbolState::DistanceExtra {
+                        length,
+                        distance_symbol,
+                    }));
+                }
+                let extra = if extra_bits > 0 {
+                    self.stream.pop_bits(extra_bits).unwrap() as u16
+                } else {
+                    0
+                };
+                let distance = base + extra;
+                if distance as usize > self.window.len() {
+                    return Err(DeflateError::InvalidDistance);
+                }
+                Ok(SymbolStep::Continue(SymbolState::Emitting {
+                    length,
+                    distance,
+                    emitted: 0,
+                }))
+            }
+            SymbolState::Emitting {
+                length,
+                distance,
+                emitted,
+            } => {
+                let index = self
+                    .window
+                    .len()
+                    .checked_sub(distance as usize)
+                    .ok_or(DeflateError::InvalidDistance)?;
+                let byte = self.window[index];
+                self.push_window(byte);
+
+                let emitted = emitted + 1;
+                let next = if emitted == length {
+                    SymbolState::Ready
+                } else {
+                    SymbolState::Emitting {
+                        length,
+                        distance,
+                        emitted,
+                    }
+                };
+                Ok(SymbolStep::Byte(byte, next))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Inflate, InflateStatus};
+    use crate::encoder::{DeflateMode, GzipWriter};
+    use crate::gzip::GzipFile;
+
+    // Feeds 'compressed' one byte at a time and drains into tiny
+    // 3-byte output buffers, exercising resumption mid-symbol and
+    // mid-back-reference on every single step.
+    fn inflate_in_tiny_chunks(compressed: &[u8]) -> Vec<u8> {
+        let mut inflate = Inflate::new();
+        let mut output = Vec::new();
+        let mut scratch = [0u8; 3];
+        let mut offset = 0;
+
+        loop {
+            let chunk = &compressed[offset..(offset + 1).min(compressed.len())];
+            let fed_new_byte = !chunk.is_empty();
+            offset += chunk.len();
+
+            let mut repeat = false;
+            loop {
+                let progress = inflate.decompress_data(chunk, &mut scratch, repeat).unwrap();
+                output.extend_from_slice(&scratch[..progress.output_written]);
+
+                match progress.status {
+                    InflateStatus::OutputFull => repeat = true,
+                    InflateStatus::NeedInput => break,
+                    InflateStatus::Done => return output,
+                }
+            }
+
+            assert!(
+                fed_new_byte,
+                "ran out of input without the stream signaling Done"
+            );
+        }
+    }
+
+    #[test]
+    fn test_streaming_inflate_matches_one_shot_decompress() {
+        let data = b"the quick brown fox jumps over the lazy dog ".repeat(50);
+        let gzip_bytes = GzipWriter::new(DeflateMode::Dynamic).build(&data);
+        let file = GzipFile::build(&gzip_bytes).unwrap();
+
+        let streamed = inflate_in_tiny_chunks(&file.deflate);
+
+        assert_eq!(streamed, data);
+    }
+
+    #[test]
+    fn test_streaming_inflate_stored_block() {
+        let data = b"hello, streaming world".to_vec();
+        let gzip_bytes = GzipWriter::new(DeflateMode::Stored).build(&data);
+        let file = GzipFile::build(&gzip_bytes).unwrap();
+
+        let streamed = inflate_in_tiny_chunks(&file.deflate);
+
+        assert_eq!(streamed, data);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod bitstream;
+pub mod crc;
+pub mod deflate;
+pub mod encoder;
+pub mod gzip;
+pub mod inflate;
+pub mod lz77;
+pub mod parallel;
+pub mod prefix;
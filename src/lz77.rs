@@ -0,0 +1,281 @@
+/// The shortest match DEFLATE can encode as a length/distance pair
+/// (RFC 1951 section 3.2.5); anything shorter is cheaper as literals.
+const MIN_MATCH: usize = 3;
+/// The longest match a single length code can express.
+const MAX_MATCH: usize = 258;
+/// The sliding window size: how far back a match may point.
+const MAX_DISTANCE: usize = 32768;
+
+/// A single LZ77-compressed symbol, produced by 'LzMatcher::tokenize'
+/// and consumed by the encoder to pick literal/length/distance codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Literal(u8),
+    Match { length: u16, distance: u16 },
+}
+
+/// Tuning knobs for 'LzMatcher', mirroring flate3's 'Options'.
+///
+/// # Fields
+///
+/// * 'probe_max' - The maximum number of earlier positions to walk down
+///         a hash chain looking for a longer match. Higher values find
+///         better matches at the cost of time.
+/// * 'lazy_match' - Whether to check the match one position ahead before
+///         committing to the match at the current position, emitting a
+///         literal and deferring when the next position's match is
+///         strictly longer.
+#[derive(Debug, Clone, Copy)]
+pub struct LzOptions {
+    pub probe_max: usize,
+    pub lazy_match: bool,
+}
+
+impl Default for LzOptions {
+    fn default() -> Self {
+        Self {
+            probe_max: 128,
+            lazy_match: true,
+        }
+    }
+}
+
+/// Finds LZ77 literal/length-distance tokens over a 32 KiB window using
+/// a 3-byte rolling hash into a chain of earlier positions, as in
+/// zlib-rs's 'hash_calc'/'longest_match'.
+///
+/// # Fields
+///
+/// * 'options' - The probe depth and lazy-matching behavior to search
+///         with.
+#[derive(Debug, Clone, Copy)]
+pub struct LzMatcher {
+    options: LzOptions,
+}
+
+impl LzMatcher {
+    /// Creates a matcher that will search with 'options'.
+    pub fn new(options: LzOptions) -> Self {
+        Self { options }
+    }
+
+    /// Tokenizes 'data' into literals and length/distance matches.
+    ///
+    /// # Arguments
+    ///
+    /// * 'data' - The raw bytes to search for repeated substrings in.
+    ///
+    /// # Returns
+    ///
+    /// The input as a sequence of 'Token's, in order.
+    pub fn tokenize(&self, data: &[u8]) -> Vec<Token> {
+        let mut chain = HashChain::new(data.len());
+        let mut tokens = Vec::new();
+
+        let mut pos = 0;
+        // When lazy matching defers, it already computed the next
+        // position's longest_match() as part of deciding to defer;
+        // stashed here so the next loop iteration doesn't redo that
+        // search against an unchanged hash chain.
+        let mut lookahead: Option<Option<(usize, usize)>> = None;
+        while pos < data.len() {
+            let found = lookahead
+                .take()
+                .unwrap_or_else(|| self.longest_match(data, pos, &chain));
+            chain.insert(data, pos);
+
+            let matched = match found {
+                Some((length, _)) if self.options.lazy_match && pos + 1 < data.len() => {
+                    // Lazy matching: a strictly longer match starting one
+                    // byte later is worth a single sacrificed literal here.
+                    let next = self.longest_match(data, pos + 1, &chain);
+                    match next {
+                        Some((next_length, _)) if next_length > length => {
+                            lookahead = Some(next);
+                            None
+                        }
+                        _ => found,
+                    }
+                }
+                _ => found,
+            };
+
+            match matched {
+                Some((length, distance)) => {
+                    tokens.push(Token::Match {
+                        length: length as u16,
+                        distance: distance as u16,
+                    });
+                    for offset in 1..length {
+                        chain.insert(data, pos + offset);
+                    }
+                    pos += length;
+                }
+                None => {
+                    tokens.push(Token::Literal(data[pos]));
+                    pos += 1;
+                }
+            }
+        }
+
+        tokens
+    }
+
+    // Walks the hash chain at 'pos' up to 'probe_max' steps, returning
+    // the longest match found as (length, distance), or None if nothing
+    // at least MIN_MATCH bytes long is within range.
+    fn longest_match(&self, data: &[u8], pos: usize, chain: &HashChain) -> Option<(usize, usize)> {
+        if pos + MIN_MATCH > data.len() {
+            return None;
+        }
+
+        let max_length = (data.len() - pos).min(MAX_MATCH);
+        let mut best_length = 0;
+        let mut best_distance = 0;
+
+        let mut candidate = chain.head(data, pos);
+        let mut probes = 0;
+        while let Some(candidate_pos) = candidate {
+            if probes >= self.options.probe_max {
+                break;
+            }
+            probes += 1;
+
+            let distance = pos - candidate_pos;
+            if distance > MAX_DISTANCE {
+                break;
+            }
+
+            let length = match_length(data, candidate_pos, pos, max_length);
+            if length > best_length {
+                best_length = length;
+                best_distance = distance;
+                if length >= max_length {
+                    break;
+                }
+            }
+
+            candidate = chain.prev(candidate_pos);
+        }
+
+        (best_length >= MIN_MATCH).then_some((best_length, best_distance))
+    }
+}
+
+// Returns how many bytes starting at 'a' and 'b' agree, capped at
+// 'max_length'.
+fn match_length(data: &[u8], a: usize, b: usize, max_length: usize) -> usize {
+    let mut length = 0;
+    while length < max_length && data[a + length] == data[b + length] {
+        length += 1;
+    }
+    length
+}
+
+const HASH_BITS: u32 = 15;
+const HASH_SIZE: usize = 1 << HASH_BITS;
+const HASH_MASK: u32 = (HASH_SIZE - 1) as u32;
+
+// A 'head[hash] -> last_position' table plus a 'prev[position] ->
+// earlier_position' chain, the classic DEFLATE match-finder structure:
+// 'head' gives the most recent position any given 3-byte hash was seen
+// at, and 'prev' threads every earlier occurrence of that same hash
+// together so 'longest_match' can walk them from newest to oldest.
+struct HashChain {
+    head: Vec<Option<usize>>,
+    prev: Vec<Option<usize>>,
+}
+
+impl HashChain {
+    fn new(len: usize) -> Self {
+        Self {
+            head: vec![None; HASH_SIZE],
+            prev: vec![None; len],
+        }
+    }
+
+    fn head(&self, data: &[u8], pos: usize) -> Option<usize> {
+        if pos + MIN_MATCH > data.len() {
+            return None;
+        }
+        self.head[hash_calc(data, pos)]
+    }
+
+    fn prev(&self, pos: usize) -> Option<usize> {
+        self.prev[pos]
+    }
+
+    // Records 'pos' as the newest occurrence of its 3-byte hash, chaining
+    // whatever was previously the newest occurrence behind it.
+    fn insert(&mut self, data: &[u8], pos: usize) {
+        if pos + MIN_MATCH > data.len() {
+            return;
+        }
+        let hash = hash_calc(data, pos);
+        self.prev[pos] = self.head[hash];
+        self.head[hash] = Some(pos);
+    }
+}
+
+// Hashes the 3 bytes starting at 'pos' into a HASH_BITS-wide bucket.
+fn hash_calc(data: &[u8], pos: usize) -> usize {
+    let h = ((data[pos] as u32) << 10) ^ ((data[pos + 1] as u32) << 5) ^ (data[pos + 2] as u32);
+    (h & HASH_MASK) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LzMatcher, LzOptions, Token};
+
+    fn tokenize(data: &[u8]) -> Vec<Token> {
+        LzMatcher::new(LzOptions::default()).tokenize(data)
+    }
+
+    fn reconstruct(tokens: &[Token]) -> Vec<u8> {
+        let mut output = Vec::new();
+        for token in tokens {
+            match *token {
+                Token::Literal(byte) => output.push(byte),
+                Token::Match { length, distance } => {
+                    let start = output.len() - distance as usize;
+                    for i in 0..length as usize {
+                        output.push(output[start + i]);
+                    }
+                }
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn test_tokenize_round_trips() {
+        let data = b"the quick brown fox jumps over the lazy dog the quick brown fox";
+        let tokens = tokenize(data);
+
+        assert_eq!(reconstruct(&tokens), data);
+        assert!(tokens
+            .iter()
+            .any(|t| matches!(t, Token::Match { .. })));
+    }
+
+    #[test]
+    fn test_tokenize_no_repeats_is_all_literals() {
+        let data = b"abcdefg";
+        let tokens = tokenize(data);
+
+        assert_eq!(tokens.len(), data.len());
+        assert!(tokens.iter().all(|t| matches!(t, Token::Literal(_))));
+    }
+
+    #[test]
+    fn test_tokenize_respects_max_match_length() {
+        let data = vec![b'a'; 600];
+        let tokens = tokenize(&data);
+
+        assert_eq!(reconstruct(&tokens), data);
+        assert!(tokens.iter().all(|t| match t {
+            Token::Match { length, .. } => *length <= 258,
+            Token::Literal(_) => true,
+        }));
+    }
+}
@@ -0,0 +1,225 @@
+use std::thread;
+
+use crate::bitstream::BitStream;
+use crate::encoder::{encode_block, DeflateMode};
+use crate::lz77::LzOptions;
+
+/// The default target size of a block handed to one worker thread.
+/// Large enough that per-block overhead (a dynamic Huffman table, a
+/// block header) stays negligible; small enough to keep the thread pool
+/// busy on inputs of a few megabytes.
+const DEFAULT_BLOCK_SIZE: usize = 256 * 1024;
+
+/// How many bytes 'dynamic_block_size' samples at a time when watching
+/// for a shift in the input's local entropy.
+const ENTROPY_SAMPLE: usize = 4096;
+
+/// How much a sample's entropy estimate (bits per byte, 0.0..=8.0) must
+/// move from the running block's average before a block is flushed
+/// early, ahead of 'block_size'.
+const ENTROPY_SHIFT_THRESHOLD: f64 = 1.5;
+
+/// A block-parallel DEFLATE compressor: splits the input into
+/// independent blocks and compresses each on its own thread, then
+/// concatenates the resulting bit-packed blocks in order.
+///
+/// Each block is tokenized and Huffman-coded entirely on its own
+/// slice of the input, so back-references never cross a block
+/// boundary — the window always resets at every block. This is what
+/// makes the blocks independent enough to compress in parallel in the
+/// first place, at the cost of missing matches that would have spanned
+/// a boundary in a single-threaded encode — a small ratio loss that
+/// grows as 'block_size' shrinks. There is no mode that preserves the
+/// window across blocks; doing so would serialize the LZ77 search that
+/// makes this encoder worth using.
+///
+/// # Fields
+///
+/// * 'mode' - The Huffman strategy each block is encoded with.
+/// * 'lz_options' - The LZ77 match finder's probe depth and lazy-match
+///         behavior, applied independently within each block.
+/// * 'block_size' - The target number of bytes per block, before
+///         'dynamic_block_size' may flush one early.
+#[derive(Debug, Clone, Copy)]
+pub struct ParallelEncoder {
+    pub mode: DeflateMode,
+    pub lz_options: LzOptions,
+    pub block_size: usize,
+}
+
+impl Default for ParallelEncoder {
+    fn default() -> Self {
+        Self::new(DeflateMode::Dynamic)
+    }
+}
+
+impl ParallelEncoder {
+    /// Creates a parallel encoder using 'mode' for every block, the
+    /// default LZ77 tuning, and 'DEFAULT_BLOCK_SIZE' blocks.
+    pub fn new(mode: DeflateMode) -> Self {
+        Self {
+            mode,
+            lz_options: LzOptions::default(),
+            block_size: DEFAULT_BLOCK_SIZE,
+        }
+    }
+
+    /// Creates a parallel encoder with an explicit target block size,
+    /// using 'mode' and the default LZ77 tuning for every block.
+    pub fn with_block_size(mode: DeflateMode, block_size: usize) -> Self {
+        Self {
+            block_size,
+            ..Self::new(mode)
+        }
+    }
+
+    /// Compresses 'data' into a single DEFLATE stream, splitting it into
+    /// independent blocks and encoding them across a scoped thread pool.
+    ///
+    /// # Arguments
+    ///
+    /// * 'data' - The raw bytes to compress.
+    ///
+    /// # Returns
+    ///
+    /// The DEFLATE-compressed byte stream, byte-identical in structure to
+    /// what a single-threaded encode over the same blocks would produce,
+    /// just split across more (and smaller) blocks, each with its window
+    /// reset at the boundary.
+    pub fn encode(&self, data: &[u8]) -> Vec<u8> {
+        let chunks = dynamic_block_size(data, self.block_size);
+        if chunks.is_empty() {
+            return encode_block(self.mode, self.lz_options, data, true).into_bytes();
+        }
+        let last = chunks.len() - 1;
+
+        let block_streams = thread::scope(|scope| {
+            let handles: Vec<_> = chunks
+                .iter()
+                .enumerate()
+                .map(|(index, &chunk)| {
+                    let mode = self.mode;
+                    let lz_options = self.lz_options;
+                    scope.spawn(move || encode_block(mode, lz_options, chunk, index == last))
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("encoder thread panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        let mut stream = BitStream::new();
+        for block_stream in block_streams {
+            stream.append(block_stream);
+        }
+        stream.into_bytes()
+    }
+}
+
+/// Splits 'data' into blocks no larger than 'block_size', flushing a
+/// block early whenever a sampled window's byte-frequency entropy has
+/// shifted sharply from the block-so-far's average — a cheap proxy for
+/// "this looks like a different kind of data now", so a block doesn't
+/// straddle, say, a run of incompressible data and a run of highly
+/// repetitive text.
+///
+/// # Arguments
+///
+/// * 'data' - The bytes to split.
+/// * 'block_size' - The maximum size of any one block.
+fn dynamic_block_size(data: &[u8], block_size: usize) -> Vec<&[u8]> {
+    if data.is_empty() || block_size == 0 {
+        return Vec::new();
+    }
+
+    let mut blocks = Vec::new();
+    let mut start = 0;
+
+    while start < data.len() {
+        let cap = (start + block_size).min(data.len());
+        let mut end = cap;
+        let mut running_entropy = sample_entropy(&data[start..(start + ENTROPY_SAMPLE).min(cap)]);
+
+        let mut cursor = start + ENTROPY_SAMPLE;
+        while cursor < cap {
+            let sample_end = (cursor + ENTROPY_SAMPLE).min(cap);
+            let entropy = sample_entropy(&data[cursor..sample_end]);
+            if (entropy - running_entropy).abs() > ENTROPY_SHIFT_THRESHOLD {
+                end = cursor;
+                break;
+            }
+            running_entropy = (running_entropy + entropy) / 2.0;
+            cursor = sample_end;
+        }
+
+        blocks.push(&data[start..end]);
+        start = end;
+    }
+
+    blocks
+}
+
+/// Estimates the Shannon entropy of 'sample' in bits per byte, treating
+/// each byte value as an independent symbol. Zero for an empty sample.
+fn sample_entropy(sample: &[u8]) -> f64 {
+    if sample.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts = [0u32; 256];
+    for &byte in sample {
+        counts[byte as usize] += 1;
+    }
+
+    let len = sample.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let probability = count as f64 / len;
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dynamic_block_size, ParallelEncoder};
+    use crate::deflate::DeflateBlock;
+    use crate::encoder::DeflateMode;
+
+    #[test]
+    fn test_dynamic_block_size_respects_max_and_covers_input() {
+        let data = vec![b'x'; 10_000];
+        let blocks = dynamic_block_size(&data, 4096);
+
+        assert!(blocks.iter().all(|block| block.len() <= 4096));
+        assert_eq!(
+            blocks.iter().map(|b| b.len()).sum::<usize>(),
+            data.len()
+        );
+    }
+
+    #[test]
+    fn test_parallel_encode_round_trips() {
+        let data = b"the quick brown fox jumps over the lazy dog ".repeat(500);
+        let encoder = ParallelEncoder::with_block_size(DeflateMode::Dynamic, 1024);
+
+        let compressed = encoder.encode(&data);
+        let decompressed = DeflateBlock::build(&compressed).unwrap().decompress().unwrap();
+
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn test_parallel_encode_empty_input() {
+        let encoder = ParallelEncoder::new(DeflateMode::Dynamic);
+
+        let compressed = encoder.encode(&[]);
+        let decompressed = DeflateBlock::build(&compressed).unwrap().decompress().unwrap();
+
+        assert_eq!(decompressed, Vec::<u8>::new());
+    }
+}
@@ -1,7 +1,12 @@
-use std::{cmp::Ordering, collections::BinaryHeap, fmt::Display};
+use std::{cmp::Ordering, fmt::Display};
 
 use crate::bitstream::BitIndex;
 
+/// RFC 1951 section 3.2.6: fixed blocks give every one of the 30
+/// distance codes a flat 5-bit length, since there is no per-block table
+/// to transmit.
+pub const FIXED_DISTANCE_LENGTHS: [u8; 30] = [5; 30];
+
 pub const FIXED_CODE_LENGTHS: [u8; 288] = [
     8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8,
     8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8,
@@ -274,43 +279,54 @@ impl PrefixTree {
         }
 
         let mut leaves = Vec::with_capacity(code_lengths.len());
-
-        let mut nodes_left = BinaryHeap::new();
-        let mut nodes_right = BinaryHeap::new();
+        let mut root = Node::new();
 
         for (symbol, code) in codes.iter().enumerate() {
             if let Some(code) = code {
+                let length = code_lengths[symbol];
                 let node = Node {
                     value: Some(symbol),
                     significance: *code as u64,
-                    code: Code::from(*code, code_lengths[symbol]),
+                    code: Code::from(*code, length),
                     left: None,
                     right: None,
                 };
 
-                leaves.push(node.clone());
-                match code.bit_index(code_lengths[symbol] - 1) {
-                    0 => nodes_left.push(Box::new(node.clone())),
-                    1 => nodes_right.push(Box::new(node.clone())),
-                    _ => {}
-                }
+                leaves.push(node);
+                insert_leaf(&mut root, *code, length, symbol);
             }
         }
 
-        let root = Node {
-            value: None,
-            significance: 0,
-            code: Code::new(),
-            left: Some(collect_from_heap(&mut nodes_left)),
-            right: Some(collect_from_heap(&mut nodes_right)),
-        };
-
         Self {
             root: root.clone(),
             leaves,
             current: Box::new(root),
         }
     }
+
+    /// Generates a length-limited prefix code tree from symbol
+    /// frequencies using the package-merge algorithm, guaranteeing no
+    /// code exceeds 'max_len' bits, which a plain Huffman tree cannot
+    /// promise.
+    ///
+    /// # Arguments
+    ///
+    /// * 'frequencies' - The frequency (weight) of each symbol, indexed
+    ///         by symbol. A frequency of 0 means the symbol is absent and
+    ///         will receive no code.
+    /// * 'max_len' - The maximum number of bits any generated code may
+    ///         use.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of PrefixTree whose codes are both canonical
+    /// (ordered the same way 'from_lengths' would produce) and bounded to
+    /// 'max_len' bits.
+    pub fn from_frequencies(frequencies: &[u64], max_len: u8) -> Self {
+        let lengths = package_merge_lengths(frequencies, max_len);
+        Self::from_lengths(&lengths)
+    }
+
     pub fn walk(&mut self, direction: u8) -> Option<usize> {
         let normalized_direction: u8 = match direction {
             0 => 0,
@@ -352,29 +368,110 @@ impl PrefixTree {
     }
 }
 
-fn collect_from_heap(heap: &mut BinaryHeap<Box<Node>>) -> Box<Node> {
-    while heap.len() > 1 {
-        let node_1 = heap.pop().unwrap();
-        let node_2 = heap.pop().unwrap();
-
-        let parent_code = node_1.code.buffer >> 1;
-        let parent_len = match node_1.code.length {
-            v if v != 0 => v - 1,
-            _ => 0,
+// Walks 'root' bit by bit according to 'code' (most significant bit
+// first, matching the order 'PrefixTree::walk' is driven in), creating
+// any missing branch nodes along the way, and marks the final node as a
+// leaf holding 'symbol'.
+fn insert_leaf(root: &mut Node, code: u32, length: u8, symbol: usize) {
+    let mut current = root;
+
+    for i in (0..length).rev() {
+        let branch = if code.bit_index(i) == 0 {
+            &mut current.left
+        } else {
+            &mut current.right
         };
 
-        let parent = Node {
-            value: None,
-            significance: parent_code as u64,
-            code: Code::from(parent_code, parent_len),
-            left: Some(node_2),
-            right: Some(node_1),
-        };
+        current = &mut **branch.get_or_insert_with(|| Box::new(Node::new()));
+    }
+
+    current.value = Some(symbol);
+}
 
-        heap.push(Box::new(parent));
+// Derives length-limited code lengths from 'frequencies' via
+// package-merge (Larmore & Hirschberg's coin-collector's algorithm).
+//
+// 'S' is the list of leaf items, one per non-zero-frequency symbol,
+// ascending by weight (frequency). 'packages' starts as a copy of 'S'
+// and is rebuilt 'max_len - 1' times: each pass pairs up adjacent
+// 'packages' items into parent nodes (dropping an odd item out, as it
+// cannot be paired), then merges that list of parents back in with 'S'
+// to form the next weight-sorted 'packages'. A package's Node.left and
+// Node.right point at the two items combined into it, so after the loop
+// each symbol's code length is just how many of the first '2n - 2'
+// packages contain that symbol as a descendant leaf.
+fn package_merge_lengths(frequencies: &[u64], max_len: u8) -> Vec<u8> {
+    let mut lengths = vec![0u8; frequencies.len()];
+
+    let mut s: Vec<Node> = frequencies
+        .iter()
+        .enumerate()
+        .filter(|&(_, &freq)| freq > 0)
+        .map(|(symbol, &freq)| Node {
+            value: Some(symbol),
+            significance: freq,
+            code: Code::new(),
+            left: None,
+            right: None,
+        })
+        .collect();
+    s.sort_by_key(|node| node.significance);
+
+    let n = s.len();
+    if n == 0 {
+        return lengths;
+    }
+    if n == 1 {
+        lengths[s[0].value.unwrap()] = 1;
+        return lengths;
+    }
+
+    let mut packages = s.clone();
+
+    for _ in 0..(max_len.saturating_sub(1)) {
+        let mut paired = Vec::with_capacity(packages.len() / 2);
+        let mut pairs = packages.into_iter();
+        while let (Some(first), Some(second)) = (pairs.next(), pairs.next()) {
+            paired.push(Node {
+                value: None,
+                significance: first.significance + second.significance,
+                code: Code::new(),
+                left: Some(Box::new(first)),
+                right: Some(Box::new(second)),
+            });
+        }
+
+        let mut merged = paired;
+        merged.extend(s.clone());
+        merged.sort_by_key(|node| node.significance);
+        packages = merged;
+    }
+
+    let keep = (2 * n - 2).min(packages.len());
+    for package in &packages[..keep] {
+        for symbol in leaf_symbols(package) {
+            lengths[symbol] += 1;
+        }
     }
 
-    heap.pop().unwrap()
+    lengths
+}
+
+// Collects the symbols of every leaf beneath 'node' (or just 'node'
+// itself, if it is already a leaf).
+fn leaf_symbols(node: &Node) -> Vec<usize> {
+    if let Some(symbol) = node.value {
+        return vec![symbol];
+    }
+
+    let mut symbols = Vec::new();
+    if let Some(left) = &node.left {
+        symbols.extend(leaf_symbols(left));
+    }
+    if let Some(right) = &node.right {
+        symbols.extend(leaf_symbols(right));
+    }
+    symbols
 }
 
 #[cfg(test)]
@@ -430,4 +527,26 @@ mod tests {
 
         assert_eq!(results, vec![0, 1, 2, 3, 4, 5, 6, 7])
     }
+    #[test]
+    fn test_from_frequencies_respects_max_len() {
+        // A heavily skewed distribution: plain Huffman would give symbol
+        // 0 a 1-bit code and symbol 9 an 8-bit code or longer, but
+        // package-merge must keep every code within max_len bits.
+        let frequencies: Vec<u64> = (0..10).map(|i| 1u64 << i).collect();
+        let max_len = 4;
+
+        let mut tree = PrefixTree::from_frequencies(&frequencies, max_len);
+
+        assert!(tree.leaves.iter().all(|leaf| leaf.code.length <= max_len));
+
+        // Every symbol should still round-trip through walk().
+        for leaf in tree.leaves.clone() {
+            let symbol = leaf.value.unwrap();
+            let mut result = None;
+            for i in (0..leaf.code.length).rev() {
+                result = tree.walk(((leaf.code.buffer >> i) & 1) as u8);
+            }
+            assert_eq!(result, Some(symbol));
+        }
+    }
 }